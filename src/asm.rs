@@ -0,0 +1,779 @@
+use crate::cpu::START_ADDRESS;
+use crate::platform::Platform;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// how deep includes can be nested, which also stops include cycles
+const MAX_INCLUDE_DEPTH: usize = 16;
+const MNEMONICS: [&str; 35] = [
+    "CLS", "RET", "SCR", "SCL", "EXIT", "LOW", "HIGH", "AUDIO", "SCD", "SCU", "SYS", "JP", "CALL",
+    "SE", "SNE", "SAVE", "LOAD", "LD", "ADD", "OR", "AND", "XOR", "SUB", "SUBN", "SHR", "SHL",
+    "RND", "DRW", "SKP", "SKNP", "PLANE", "PITCH", "DB", "DW", "INCLUDE",
+];
+
+/// Error in an assembly source, lines and columns start at 1.
+///
+/// The line is 0 when the file itself couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        if self.line > 0 {
+            write!(f, "{}:{}:", self.line, self.column)?;
+        }
+
+        write!(f, " {}", self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// Assembles source text into a rom loaded at `START_ADDRESS`.
+///
+/// The syntax is the one [`disasm::listing`](crate::disasm::listing) prints:
+/// one instruction per line, `;` comments, `name:` labels, `name = expr`
+/// constants, `db`/`dw` data and `include "file"`. Includes are relative
+/// to the working directory.
+pub fn assemble(source: &str, platform: Platform) -> Result<Vec<u8>, AsmError> {
+    let mut lines = Vec::new();
+    read_lines(source, None, 0, &mut lines)?;
+
+    Assembler::new(platform, &lines).run()
+}
+
+/// Like [`assemble`] with includes relative to the file.
+pub fn assemble_file(path: &Path, platform: Platform) -> Result<Vec<u8>, AsmError> {
+    let mut lines = Vec::new();
+    let source = std::fs::read_to_string(path).map_err(|err| AsmError {
+        file: Some(path.to_path_buf()),
+        line: 0,
+        column: 0,
+        message: err.to_string(),
+    })?;
+    read_lines(&source, Some(Rc::from(path)), 0, &mut lines)?;
+
+    Assembler::new(platform, &lines).run()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    Str(String),
+    Punct(&'static str),
+}
+
+#[derive(Clone, Debug)]
+struct Spanned {
+    token: Token,
+    column: usize,
+}
+
+struct Line {
+    file: Option<Rc<Path>>,
+    number: usize,
+    tokens: Vec<Spanned>,
+}
+
+impl Line {
+    fn error(&self, column: usize, message: impl Into<String>) -> AsmError {
+        AsmError {
+            file: self.file.as_deref().map(Path::to_path_buf),
+            line: self.number,
+            column,
+            message: message.into(),
+        }
+    }
+
+    // column right after the last token, for things missing at the end
+    fn end_column(&self) -> usize {
+        self.tokens.last().map_or(1, |last| last.column + 1)
+    }
+}
+
+// tokenizes every line and splices includes in
+fn read_lines(
+    source: &str,
+    file: Option<Rc<Path>>,
+    depth: usize,
+    lines: &mut Vec<Line>,
+) -> Result<(), AsmError> {
+    for (index, text) in source.lines().enumerate() {
+        let mut line = Line {
+            file: file.clone(),
+            number: index + 1,
+            tokens: Vec::new(),
+        };
+        line.tokens = tokenize(text, &line)?;
+
+        match &line.tokens[..] {
+            [Spanned {
+                token: Token::Ident(name),
+                column,
+            }, rest @ ..]
+                if name.eq_ignore_ascii_case("include") =>
+            {
+                let path = match rest {
+                    [Spanned {
+                        token: Token::Str(path),
+                        ..
+                    }] => path,
+                    _ => return Err(line.error(*column, "include needs a quoted path")),
+                };
+                if depth == MAX_INCLUDE_DEPTH {
+                    return Err(line.error(*column, "includes are nested too deep"));
+                }
+
+                let path = match file.as_deref().and_then(Path::parent) {
+                    Some(dir) => dir.join(path),
+                    None => PathBuf::from(path),
+                };
+                let source = std::fs::read_to_string(&path).map_err(|err| {
+                    line.error(
+                        *column,
+                        format!("can't include {}: {}", path.display(), err),
+                    )
+                })?;
+
+                read_lines(&source, Some(Rc::from(path)), depth + 1, lines)?;
+            }
+            [] => (),
+            _ => lines.push(line),
+        }
+    }
+
+    Ok(())
+}
+
+fn tokenize(text: &str, line: &Line) -> Result<Vec<Spanned>, AsmError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        let column = pos + 1;
+        let word_end = |from: usize| {
+            (from..chars.len())
+                .find(|&i| {
+                    !(chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+                })
+                .unwrap_or(chars.len())
+        };
+
+        let token = if c == ';' {
+            break;
+        } else if c.is_whitespace() {
+            pos += 1;
+            continue;
+        } else if c.is_ascii_digit() {
+            let end = word_end(pos);
+            let word: String = chars[pos..end].iter().collect();
+            pos = end;
+            Token::Number(
+                parse_number(&word)
+                    .ok_or_else(|| line.error(column, format!("invalid number: {}", word)))?,
+            )
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '.' {
+            let end = word_end(pos);
+            let word = chars[pos..end].iter().collect();
+            pos = end;
+            Token::Ident(word)
+        } else if c == '"' {
+            let end = (pos + 1..chars.len())
+                .find(|&i| chars[i] == '"')
+                .ok_or_else(|| line.error(column, "unterminated string"))?;
+            let string = chars[pos + 1..end].iter().collect();
+            pos = end + 1;
+            Token::Str(string)
+        } else {
+            let pair: String = chars[pos..(pos + 2).min(chars.len())].iter().collect();
+            let punct = match (pair.as_str(), c) {
+                ("<<", _) => "<<",
+                (">>", _) => ">>",
+                (_, '+') => "+",
+                (_, '-') => "-",
+                (_, '*') => "*",
+                (_, '/') => "/",
+                (_, '%') => "%",
+                (_, '&') => "&",
+                (_, '|') => "|",
+                (_, '^') => "^",
+                (_, '~') => "~",
+                (_, '(') => "(",
+                (_, ')') => ")",
+                (_, '[') => "[",
+                (_, ']') => "]",
+                (_, ',') => ",",
+                (_, ':') => ":",
+                (_, '=') => "=",
+                _ => return Err(line.error(column, format!("unexpected character: {}", c))),
+            };
+            pos += punct.len();
+            Token::Punct(punct)
+        };
+
+        tokens.push(Spanned { token, column });
+    }
+
+    Ok(tokens)
+}
+
+fn parse_number(word: &str) -> Option<i64> {
+    let lower = word.to_ascii_lowercase();
+
+    if let Some(hex) = lower.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = lower.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2).ok()
+    } else {
+        lower.parse().ok()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Needs {
+    Any,
+    SuperChip,
+    XoChip,
+}
+
+// an expression and the line it's on
+#[derive(Clone, Copy)]
+struct Expr<'a> {
+    tokens: &'a [Spanned],
+    line: &'a Line,
+}
+
+impl<'a> Expr<'a> {
+    fn column(&self) -> usize {
+        self.tokens[0].column
+    }
+}
+
+enum Operand<'a> {
+    Reg(u16),
+    I,
+    IndirectI,
+    Dt,
+    St,
+    K,
+    F,
+    Hf,
+    B,
+    R,
+    Long(Expr<'a>),
+    Value(Expr<'a>),
+}
+
+enum Symbol<'a> {
+    Address(u16),
+    Constant(Expr<'a>),
+}
+
+struct Statement<'a> {
+    line: &'a Line,
+    mnemonic: String,
+    column: usize,
+    operands: Vec<&'a [Spanned]>,
+}
+
+struct Assembler<'a> {
+    platform: Platform,
+    lines: &'a [Line],
+    symbols: HashMap<String, Symbol<'a>>,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(platform: Platform, lines: &'a [Line]) -> Self {
+        Self {
+            platform,
+            lines,
+            symbols: HashMap::new(),
+        }
+    }
+
+    fn run(mut self) -> Result<Vec<u8>, AsmError> {
+        let statements = self.collect_symbols()?;
+        let mut rom = Vec::new();
+
+        for statement in &statements {
+            rom.extend(self.encode(statement)?);
+        }
+
+        Ok(rom)
+    }
+
+    // first pass, gives every label its address
+    fn collect_symbols(&mut self) -> Result<Vec<Statement<'a>>, AsmError> {
+        let mut statements = Vec::new();
+        let mut addr = START_ADDRESS as usize;
+
+        for line in self.lines {
+            let mut tokens = &line.tokens[..];
+
+            if let [Spanned {
+                token: Token::Ident(name),
+                column,
+            }, Spanned {
+                token: Token::Punct(":"),
+                ..
+            }, rest @ ..] = tokens
+            {
+                self.define(name, Symbol::Address(addr as u16), line, *column)?;
+                tokens = rest;
+            }
+
+            let (mnemonic, column, rest) = match tokens {
+                [] => continue,
+                [Spanned {
+                    token: Token::Ident(name),
+                    column,
+                }, second, rest @ ..]
+                    if is_assignment(&second.token) =>
+                {
+                    if rest.is_empty() {
+                        return Err(line.error(line.end_column(), "missing value"));
+                    }
+                    self.define(
+                        name,
+                        Symbol::Constant(Expr { tokens: rest, line }),
+                        line,
+                        *column,
+                    )?;
+                    continue;
+                }
+                [Spanned {
+                    token: Token::Ident(name),
+                    column,
+                }, rest @ ..] => (name.to_ascii_uppercase(), *column, rest),
+                [first, ..] => return Err(line.error(first.column, "expected an instruction")),
+            };
+
+            let operands = split_operands(rest, line)?;
+            let size = match mnemonic.as_str() {
+                "DB" => operands.len(),
+                "DW" => operands.len() * 2,
+                "LD" if is_long(&operands) => 4,
+                _ => 2,
+            };
+
+            statements.push(Statement {
+                line,
+                mnemonic,
+                column,
+                operands,
+            });
+
+            addr += size;
+            if addr > 0x10000 {
+                return Err(line.error(column, "program doesn't fit in 64KB"));
+            }
+        }
+
+        Ok(statements)
+    }
+
+    fn define(
+        &mut self,
+        name: &str,
+        symbol: Symbol<'a>,
+        line: &Line,
+        column: usize,
+    ) -> Result<(), AsmError> {
+        if register(name).is_some() || keyword(name).is_some() || name.eq_ignore_ascii_case("long")
+        {
+            return Err(line.error(column, format!("{} is a reserved name", name)));
+        }
+        if self.symbols.insert(name.to_string(), symbol).is_some() {
+            return Err(line.error(column, format!("{} is already defined", name)));
+        }
+
+        Ok(())
+    }
+
+    // second pass
+    fn encode(&self, statement: &Statement<'a>) -> Result<Vec<u8>, AsmError> {
+        let line = statement.line;
+        let operands: Vec<Operand> = statement
+            .operands
+            .iter()
+            .map(|tokens| operand(tokens, line))
+            .collect();
+
+        use Operand::*;
+
+        let (opcodes, needs) = match (statement.mnemonic.as_str(), &operands[..]) {
+            ("DB", values) => {
+                return values
+                    .iter()
+                    .map(|value| match value {
+                        Value(expr) => self.byte(*expr),
+                        _ => Err(line.error(statement.column, "db needs numbers")),
+                    })
+                    .collect();
+            }
+            ("DW", values) => {
+                let mut bytes = Vec::new();
+                for value in values {
+                    match value {
+                        Value(expr) => bytes.extend(self.word(*expr)?.to_be_bytes()),
+                        _ => return Err(line.error(statement.column, "dw needs numbers")),
+                    }
+                }
+                return Ok(bytes);
+            }
+            ("CLS", []) => (vec![0x00E0], Needs::Any),
+            ("RET", []) => (vec![0x00EE], Needs::Any),
+            ("SCR", []) => (vec![0x00FB], Needs::SuperChip),
+            ("SCL", []) => (vec![0x00FC], Needs::SuperChip),
+            ("EXIT", []) => (vec![0x00FD], Needs::SuperChip),
+            ("LOW", []) => (vec![0x00FE], Needs::SuperChip),
+            ("HIGH", []) => (vec![0x00FF], Needs::SuperChip),
+            ("AUDIO", []) => (vec![0xF002], Needs::XoChip),
+            ("SCD", [Value(n)]) => (vec![0x00C0 | self.nibble(*n)?], Needs::SuperChip),
+            ("SCU", [Value(n)]) => (vec![0x00D0 | self.nibble(*n)?], Needs::XoChip),
+            ("SYS", [Value(addr)]) => (vec![self.addr(*addr)?], Needs::Any),
+            ("JP", [Value(addr)]) => (vec![0x1000 | self.addr(*addr)?], Needs::Any),
+            ("JP", [Reg(0), Value(addr)]) => (vec![0xB000 | self.addr(*addr)?], Needs::Any),
+            ("CALL", [Value(addr)]) => (vec![0x2000 | self.addr(*addr)?], Needs::Any),
+            ("SE", [Reg(x), Reg(y)]) => (vec![0x5000 | x << 8 | y << 4], Needs::Any),
+            ("SE", [Reg(x), Value(nn)]) => {
+                (vec![0x3000 | x << 8 | self.byte(*nn)? as u16], Needs::Any)
+            }
+            ("SNE", [Reg(x), Reg(y)]) => (vec![0x9000 | x << 8 | y << 4], Needs::Any),
+            ("SNE", [Reg(x), Value(nn)]) => {
+                (vec![0x4000 | x << 8 | self.byte(*nn)? as u16], Needs::Any)
+            }
+            ("SAVE", [Reg(x), Reg(y)]) => (vec![0x5002 | x << 8 | y << 4], Needs::XoChip),
+            ("LOAD", [Reg(x), Reg(y)]) => (vec![0x5003 | x << 8 | y << 4], Needs::XoChip),
+            ("LD", [Reg(x), Reg(y)]) => (vec![0x8000 | x << 8 | y << 4], Needs::Any),
+            ("LD", [Reg(x), Value(nn)]) => {
+                (vec![0x6000 | x << 8 | self.byte(*nn)? as u16], Needs::Any)
+            }
+            ("LD", [I, Value(addr)]) => (vec![0xA000 | self.addr(*addr)?], Needs::Any),
+            ("LD", [I, Long(addr)]) => (vec![0xF000, self.word(*addr)?], Needs::XoChip),
+            ("LD", [Reg(x), Dt]) => (vec![0xF007 | x << 8], Needs::Any),
+            ("LD", [Reg(x), K]) => (vec![0xF00A | x << 8], Needs::Any),
+            ("LD", [Dt, Reg(x)]) => (vec![0xF015 | x << 8], Needs::Any),
+            ("LD", [St, Reg(x)]) => (vec![0xF018 | x << 8], Needs::Any),
+            ("LD", [F, Reg(x)]) => (vec![0xF029 | x << 8], Needs::Any),
+            ("LD", [Hf, Reg(x)]) => (vec![0xF030 | x << 8], Needs::SuperChip),
+            ("LD", [B, Reg(x)]) => (vec![0xF033 | x << 8], Needs::Any),
+            ("LD", [IndirectI, Reg(x)]) => (vec![0xF055 | x << 8], Needs::Any),
+            ("LD", [Reg(x), IndirectI]) => (vec![0xF065 | x << 8], Needs::Any),
+            ("LD", [R, Reg(x)]) => (vec![0xF075 | x << 8], Needs::SuperChip),
+            ("LD", [Reg(x), R]) => (vec![0xF085 | x << 8], Needs::SuperChip),
+            ("ADD", [Reg(x), Reg(y)]) => (vec![0x8004 | x << 8 | y << 4], Needs::Any),
+            ("ADD", [Reg(x), Value(nn)]) => {
+                (vec![0x7000 | x << 8 | self.byte(*nn)? as u16], Needs::Any)
+            }
+            ("ADD", [I, Reg(x)]) => (vec![0xF01E | x << 8], Needs::Any),
+            ("OR", [Reg(x), Reg(y)]) => (vec![0x8001 | x << 8 | y << 4], Needs::Any),
+            ("AND", [Reg(x), Reg(y)]) => (vec![0x8002 | x << 8 | y << 4], Needs::Any),
+            ("XOR", [Reg(x), Reg(y)]) => (vec![0x8003 | x << 8 | y << 4], Needs::Any),
+            ("SUB", [Reg(x), Reg(y)]) => (vec![0x8005 | x << 8 | y << 4], Needs::Any),
+            ("SUBN", [Reg(x), Reg(y)]) => (vec![0x8007 | x << 8 | y << 4], Needs::Any),
+            // shifting a register into itself works with either shift quirk
+            ("SHR", [Reg(x)]) => (vec![0x8006 | x << 8 | x << 4], Needs::Any),
+            ("SHR", [Reg(x), Reg(y)]) => (vec![0x8006 | x << 8 | y << 4], Needs::Any),
+            ("SHL", [Reg(x)]) => (vec![0x800E | x << 8 | x << 4], Needs::Any),
+            ("SHL", [Reg(x), Reg(y)]) => (vec![0x800E | x << 8 | y << 4], Needs::Any),
+            ("RND", [Reg(x), Value(nn)]) => {
+                (vec![0xC000 | x << 8 | self.byte(*nn)? as u16], Needs::Any)
+            }
+            ("DRW", [Reg(x), Reg(y), Value(n)]) => (
+                vec![0xD000 | x << 8 | y << 4 | self.nibble(*n)?],
+                Needs::Any,
+            ),
+            ("SKP", [Reg(x)]) => (vec![0xE09E | x << 8], Needs::Any),
+            ("SKNP", [Reg(x)]) => (vec![0xE0A1 | x << 8], Needs::Any),
+            ("PLANE", [Value(n)]) => (
+                vec![0xF001 | (self.number(*n, 0, 3)? as u16) << 8],
+                Needs::XoChip,
+            ),
+            ("PITCH", [Reg(x)]) => (vec![0xF03A | x << 8], Needs::XoChip),
+            (mnemonic, _) if MNEMONICS.contains(&mnemonic) => {
+                let message = format!("invalid operands for {}", mnemonic);
+                return Err(line.error(statement.column, message));
+            }
+            (mnemonic, _) => {
+                let message = format!("unknown instruction: {}", mnemonic);
+                return Err(line.error(statement.column, message));
+            }
+        };
+
+        let missing = match needs {
+            Needs::SuperChip if !self.platform.has_schip_opcodes() => Some("SUPER-CHIP"),
+            Needs::XoChip if !self.platform.has_xo_opcodes() => Some("XO-CHIP"),
+            _ => None,
+        };
+        if let Some(platform) = missing {
+            let message = format!("{} needs {}", statement.mnemonic, platform);
+            return Err(line.error(statement.column, message));
+        }
+
+        Ok(opcodes
+            .iter()
+            .flat_map(|opcode| opcode.to_be_bytes())
+            .collect())
+    }
+
+    fn nibble(&self, expr: Expr) -> Result<u16, AsmError> {
+        Ok(self.number(expr, 0, 0xF)? as u16)
+    }
+
+    // negative bytes are stored as two's complement
+    fn byte(&self, expr: Expr) -> Result<u8, AsmError> {
+        Ok(self.number(expr, -0x80, 0xFF)? as u8)
+    }
+
+    fn addr(&self, expr: Expr) -> Result<u16, AsmError> {
+        Ok(self.number(expr, 0, 0xFFF)? as u16)
+    }
+
+    fn word(&self, expr: Expr) -> Result<u16, AsmError> {
+        Ok(self.number(expr, -0x8000, 0xFFFF)? as u16)
+    }
+
+    fn number(&self, expr: Expr, min: i64, max: i64) -> Result<i64, AsmError> {
+        let value = self.evaluate(expr, &mut Vec::new())?;
+
+        if value < min || value > max {
+            let message = format!("{} is out of range ({} to {})", value, min, max);
+            return Err(expr.line.error(expr.column(), message));
+        }
+
+        Ok(value)
+    }
+
+    // `resolving` holds the constants being evaluated to catch cycles
+    fn evaluate(&self, expr: Expr, resolving: &mut Vec<String>) -> Result<i64, AsmError> {
+        let mut parser = ExprParser {
+            assembler: self,
+            expr,
+            pos: 0,
+            resolving,
+        };
+        let value = parser.parse(0)?;
+
+        match expr.tokens.get(parser.pos) {
+            Some(token) => Err(expr.line.error(token.column, "unexpected token")),
+            None => Ok(value),
+        }
+    }
+
+    fn symbol(
+        &self,
+        name: &str,
+        expr: Expr,
+        column: usize,
+        resolving: &mut Vec<String>,
+    ) -> Result<i64, AsmError> {
+        match self.symbols.get(name) {
+            Some(Symbol::Address(addr)) => Ok(*addr as i64),
+            Some(Symbol::Constant(_)) if resolving.iter().any(|other| other == name) => Err(expr
+                .line
+                .error(column, format!("{} is defined in terms of itself", name))),
+            Some(Symbol::Constant(value)) => {
+                resolving.push(name.to_string());
+                let result = self.evaluate(*value, resolving);
+                resolving.pop();
+
+                result
+            }
+            None => Err(expr
+                .line
+                .error(column, format!("undefined symbol: {}", name))),
+        }
+    }
+}
+
+// precedence climbing over the tokens of one operand
+struct ExprParser<'s, 'a> {
+    assembler: &'s Assembler<'a>,
+    expr: Expr<'s>,
+    pos: usize,
+    resolving: &'s mut Vec<String>,
+}
+
+impl<'s, 'a> ExprParser<'s, 'a> {
+    fn parse(&mut self, min_precedence: u8) -> Result<i64, AsmError> {
+        let mut left = self.unary()?;
+
+        while let Some(Spanned {
+            token: Token::Punct(op),
+            column,
+        }) = self.expr.tokens.get(self.pos)
+        {
+            let precedence = match *op {
+                "|" => 1,
+                "^" => 2,
+                "&" => 3,
+                "<<" | ">>" => 4,
+                "+" | "-" => 5,
+                "*" | "/" | "%" => 6,
+                _ => break,
+            };
+            if precedence < min_precedence {
+                break;
+            }
+
+            let column = *column;
+            self.pos += 1;
+            let right = self.parse(precedence + 1)?;
+
+            left = match *op {
+                "|" => left | right,
+                "^" => left ^ right,
+                "&" => left & right,
+                "<<" => left.checked_shl(right as u32).unwrap_or(0),
+                ">>" => left.checked_shr(right as u32).unwrap_or(0),
+                "+" => left.wrapping_add(right),
+                "-" => left.wrapping_sub(right),
+                "*" => left.wrapping_mul(right),
+                _ if right == 0 => return Err(self.expr.line.error(column, "division by zero")),
+                "/" => left / right,
+                _ => left % right,
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<i64, AsmError> {
+        let line = self.expr.line;
+        let Some(spanned) = self.expr.tokens.get(self.pos) else {
+            return Err(line.error(line.end_column(), "missing value"));
+        };
+        self.pos += 1;
+
+        match &spanned.token {
+            Token::Number(value) => Ok(*value),
+            Token::Ident(name) => {
+                self.assembler
+                    .symbol(name, self.expr, spanned.column, self.resolving)
+            }
+            Token::Punct("-") => Ok(self.unary()?.wrapping_neg()),
+            Token::Punct("~") => Ok(!self.unary()?),
+            Token::Punct("(") => {
+                let value = self.parse(0)?;
+
+                match self.expr.tokens.get(self.pos) {
+                    Some(Spanned {
+                        token: Token::Punct(")"),
+                        ..
+                    }) => {
+                        self.pos += 1;
+                        Ok(value)
+                    }
+                    _ => Err(line.error(spanned.column, "unclosed parenthesis")),
+                }
+            }
+            _ => Err(line.error(spanned.column, "expected a value")),
+        }
+    }
+}
+
+fn is_assignment(token: &Token) -> bool {
+    match token {
+        Token::Punct("=") => true,
+        Token::Ident(word) => word.eq_ignore_ascii_case("equ"),
+        _ => false,
+    }
+}
+
+fn split_operands<'a>(tokens: &'a [Spanned], line: &Line) -> Result<Vec<&'a [Spanned]>, AsmError> {
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let operands: Vec<&[Spanned]> = tokens
+        .split(|spanned| spanned.token == Token::Punct(","))
+        .collect();
+
+    match operands.iter().position(|operand| operand.is_empty()) {
+        Some(index) => {
+            // the comma before or after the missing operand
+            let commas: Vec<usize> = tokens
+                .iter()
+                .filter(|spanned| spanned.token == Token::Punct(","))
+                .map(|spanned| spanned.column)
+                .collect();
+            let column = *commas.get(index).unwrap_or(commas.last().unwrap());
+            Err(line.error(column, "missing operand"))
+        }
+        None => Ok(operands),
+    }
+}
+
+fn is_long(operands: &[&[Spanned]]) -> bool {
+    matches!(
+        operands,
+        [_, [Spanned { token: Token::Ident(word), .. }, ..]] if word.eq_ignore_ascii_case("long")
+    )
+}
+
+fn operand<'a>(tokens: &'a [Spanned], line: &'a Line) -> Operand<'a> {
+    let words: Vec<&Token> = tokens.iter().map(|spanned| &spanned.token).collect();
+
+    match &words[..] {
+        [Token::Ident(name)] => {
+            if let Some(x) = register(name) {
+                return Operand::Reg(x);
+            }
+            if let Some(keyword) = keyword(name) {
+                return keyword;
+            }
+        }
+        [Token::Punct("["), Token::Ident(name), Token::Punct("]")]
+            if name.eq_ignore_ascii_case("i") =>
+        {
+            return Operand::IndirectI;
+        }
+        [Token::Ident(name), _, ..] if name.eq_ignore_ascii_case("long") => {
+            return Operand::Long(Expr {
+                tokens: &tokens[1..],
+                line,
+            });
+        }
+        _ => (),
+    }
+
+    Operand::Value(Expr { tokens, line })
+}
+
+fn register(name: &str) -> Option<u16> {
+    match name.as_bytes() {
+        [b'v' | b'V', digit] => (*digit as char).to_digit(16).map(|x| x as u16),
+        _ => None,
+    }
+}
+
+fn keyword(name: &str) -> Option<Operand<'static>> {
+    match name.to_ascii_uppercase().as_str() {
+        "I" => Some(Operand::I),
+        "DT" => Some(Operand::Dt),
+        "ST" => Some(Operand::St),
+        "K" => Some(Operand::K),
+        "F" => Some(Operand::F),
+        "HF" => Some(Operand::Hf),
+        "B" => Some(Operand::B),
+        "R" => Some(Operand::R),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,140 @@
+use super::*;
+use crate::disasm;
+
+fn error_at(source: &str, platform: Platform) -> (usize, usize, String) {
+    let err = assemble(source, platform).unwrap_err();
+
+    (err.line, err.column, err.message)
+}
+
+#[test]
+fn test_instructions() {
+    let source = "
+        CLS
+        LD V0, 0x01
+        LD I, [I]
+    ";
+
+    assert!(assemble(source, Platform::Chip8).is_err());
+    assert_eq!(
+        assemble(
+            "cls\nld va, 2\nDRW V0, V1, 5\nLD [I], V3\nSHR V4\n",
+            Platform::Chip8
+        )
+        .unwrap(),
+        vec![0x00, 0xE0, 0x6A, 0x02, 0xD0, 0x15, 0xF3, 0x55, 0x84, 0x46]
+    );
+}
+
+#[test]
+fn test_labels_and_constants() {
+    let source = "
+        SPEED = 3
+        start:  LD V0, SPEED * 2 + 1   ; 7
+                CALL sub
+                JP start
+        sub:    ADD V0, -1
+                RET
+        sprite: db 0b11110000, 0x90
+                dw sprite
+    ";
+
+    assert_eq!(
+        assemble(source, Platform::Chip8).unwrap(),
+        vec![0x60, 0x07, 0x22, 0x06, 0x12, 0x00, 0x70, 0xFF, 0x00, 0xEE, 0xF0, 0x90, 0x02, 0x0A]
+    );
+}
+
+#[test]
+fn test_expressions() {
+    let source = "
+        FLAGS equ (1 << 4) | 0x3
+        MASK = FLAGS & ~1 ^ 0b10
+        db FLAGS, MASK, 10 % 4, 9 / 2, -(2 - 5)
+    ";
+
+    assert_eq!(
+        assemble(source, Platform::Chip8).unwrap(),
+        vec![0x13, 0x10, 2, 4, 3]
+    );
+}
+
+#[test]
+fn test_roundtrip_with_disassembler() {
+    let rom = [
+        0x00, 0xFF, 0xA2, 0x0A, 0x60, 0x01, 0xD0, 0x1A, 0x12, 0x00, 0x3C, 0x42, 0xFF,
+    ];
+
+    let listing = disasm::listing(&rom, Platform::SuperChip);
+
+    assert_eq!(assemble(&listing, Platform::SuperChip).unwrap(), rom);
+}
+
+#[test]
+fn test_xo_long_load() {
+    let source = "LD I, LONG data\nPLANE 3\ndata: db 1";
+
+    assert_eq!(
+        assemble(source, Platform::XoChip).unwrap(),
+        vec![0xF0, 0x00, 0x02, 0x06, 0xF3, 0x01, 0x01]
+    );
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        error_at("CLS\n  FOO V0", Platform::Chip8),
+        (2, 3, "unknown instruction: FOO".to_string())
+    );
+    assert_eq!(
+        error_at("LD V0, 256", Platform::Chip8),
+        (1, 8, "256 is out of range (-128 to 255)".to_string())
+    );
+    assert_eq!(
+        error_at("JP nowhere", Platform::Chip8),
+        (1, 4, "undefined symbol: nowhere".to_string())
+    );
+    assert_eq!(
+        error_at("LD V0,", Platform::Chip8),
+        (1, 6, "missing operand".to_string())
+    );
+    assert_eq!(
+        error_at("SE I, V0", Platform::Chip8),
+        (1, 1, "invalid operands for SE".to_string())
+    );
+    assert_eq!(
+        error_at("x:\nx:", Platform::Chip8),
+        (2, 1, "x is already defined".to_string())
+    );
+    assert_eq!(
+        error_at("X = Y\nY = X\ndb X", Platform::Chip8),
+        (2, 5, "X is defined in terms of itself".to_string())
+    );
+    assert_eq!(
+        error_at("  HIGH", Platform::Chip8),
+        (1, 3, "HIGH needs SUPER-CHIP".to_string())
+    );
+    assert_eq!(
+        error_at("db 1 $", Platform::Chip8),
+        (1, 6, "unexpected character: $".to_string())
+    );
+}
+
+#[test]
+fn test_include() {
+    let dir = std::env::temp_dir().join(format!("chip8-asm-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("main.asm"),
+        "include \"sprites.asm\"\nLD I, ball\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("sprites.asm"), "BALL_SIZE = 1\nball: db 0x80\n").unwrap();
+
+    let rom = assemble_file(&dir.join("main.asm"), Platform::Chip8);
+    let err = assemble_file(&dir.join("missing.asm"), Platform::Chip8).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(rom.unwrap(), vec![0x80, 0xA2, 0x00]);
+    assert_eq!(err.line, 0);
+}
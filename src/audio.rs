@@ -0,0 +1,106 @@
+use crate::cpu::AUDIO_PATTERN_SIZE;
+use std::f32::consts::TAU;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Sine,
+    Triangle,
+    Sawtooth,
+}
+
+impl Waveform {
+    pub fn from_name(name: &str) -> Option<Waveform> {
+        match name {
+            "square" => Some(Waveform::Square),
+            "sine" => Some(Waveform::Sine),
+            "triangle" => Some(Waveform::Triangle),
+            "sawtooth" => Some(Waveform::Sawtooth),
+            _ => None,
+        }
+    }
+
+    // phase goes from 0 to 1 over a period
+    fn sample(&self, phase: f32) -> f32 {
+        match self {
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+        }
+    }
+}
+
+// XO-CHIP 1-bit sample buffer and the rate its bits are played at
+pub type Pattern = ([u8; AUDIO_PATTERN_SIZE], f32);
+
+/// Sample generator for the sound timer beep, independent of any audio backend.
+pub struct Tone {
+    pub waveform: Waveform,
+    pub frequency: f32,
+    pub volume: f32,
+    pattern: Option<Pattern>,
+    active: bool,
+    phase: f32,
+}
+
+impl Tone {
+    pub fn new(waveform: Waveform, frequency: f32, volume: f32) -> Self {
+        Self {
+            waveform,
+            frequency,
+            volume,
+            pattern: None,
+            active: false,
+            phase: 0.0,
+        }
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    // when a pattern is set it replaces the waveform
+    pub fn set_pattern(&mut self, pattern: Option<Pattern>) {
+        self.pattern = pattern;
+    }
+
+    pub fn fill(&mut self, out: &mut [f32], sample_rate: u32) {
+        if !self.active {
+            out.fill(0.0);
+            return;
+        }
+
+        match self.pattern {
+            Some((pattern, rate)) => {
+                let bits = (AUDIO_PATTERN_SIZE * 8) as f32;
+                let step = rate / sample_rate as f32;
+
+                for sample in out.iter_mut() {
+                    let bit = self.phase as usize;
+                    let set = pattern[bit / 8] & (0b1000_0000 >> (bit % 8)) != 0;
+
+                    *sample = if set { self.volume } else { -self.volume };
+                    self.phase = (self.phase + step) % bits;
+                }
+            }
+            None => {
+                let step = self.frequency / sample_rate as f32;
+
+                for sample in out.iter_mut() {
+                    *sample = self.waveform.sample(self.phase) * self.volume;
+                    self.phase = (self.phase + step) % 1.0;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
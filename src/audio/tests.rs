@@ -0,0 +1,38 @@
+use super::*;
+
+const SAMPLE_RATE: u32 = 8000;
+
+#[test]
+fn test_silent_when_inactive() {
+    let mut tone = Tone::new(Waveform::Square, 440.0, 0.5);
+    let mut out = [1.0; 64];
+
+    tone.fill(&mut out, SAMPLE_RATE);
+
+    assert!(out.iter().all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn test_square() {
+    let mut tone = Tone::new(Waveform::Square, 1000.0, 0.5);
+    let mut out = [0.0; 8];
+    tone.set_active(true);
+
+    tone.fill(&mut out, SAMPLE_RATE);
+
+    assert_eq!(out, [0.5, 0.5, 0.5, 0.5, -0.5, -0.5, -0.5, -0.5]);
+}
+
+#[test]
+fn test_pattern() {
+    let mut tone = Tone::new(Waveform::Sine, 440.0, 0.25);
+    let mut pattern = [0; AUDIO_PATTERN_SIZE];
+    pattern[0] = 0b1010_0000;
+    let mut out = [0.0; 4];
+    tone.set_active(true);
+    tone.set_pattern(Some((pattern, SAMPLE_RATE as f32)));
+
+    tone.fill(&mut out, SAMPLE_RATE);
+
+    assert_eq!(out, [0.25, -0.25, 0.25, -0.25]);
+}
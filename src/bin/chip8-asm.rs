@@ -0,0 +1,51 @@
+use chip_8::{asm, Platform};
+use std::path::PathBuf;
+use std::process::exit;
+
+const USAGE: &str =
+    "usage: chip8-asm [--platform chip8|schip|xochip] [-o <path to rom>] <path to source>";
+
+fn main() {
+    let mut platform = Platform::Chip8;
+    let mut source_path = None;
+    let mut rom_path = None;
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--platform" => {
+                let name = args.next().unwrap_or_else(|| fail(USAGE));
+                platform = Platform::from_name(&name)
+                    .unwrap_or_else(|| fail(&format!("unknown platform: {}", name)));
+            }
+            "-o" => rom_path = Some(PathBuf::from(args.next().unwrap_or_else(|| fail(USAGE)))),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if source_path.is_none() => source_path = Some(PathBuf::from(arg)),
+            _ => fail(USAGE),
+        }
+    }
+
+    let source_path = source_path.unwrap_or_else(|| fail(USAGE));
+    // the extension makes the emulator pick the same platform
+    let rom_path = rom_path.unwrap_or_else(|| {
+        let extension = match platform {
+            Platform::Chip8 => "ch8",
+            Platform::SuperChip => "sc8",
+            Platform::XoChip => "xo8",
+        };
+        source_path.with_extension(extension)
+    });
+
+    let rom =
+        asm::assemble_file(&source_path, platform).unwrap_or_else(|err| fail(&err.to_string()));
+    std::fs::write(&rom_path, &rom)
+        .unwrap_or_else(|err| fail(&format!("can't write {}: {}", rom_path.display(), err)));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1);
+}
@@ -0,0 +1,42 @@
+use chip_8::{disasm, Platform};
+use std::path::Path;
+use std::process::exit;
+
+const USAGE: &str = "usage: chip8-disasm [--platform chip8|schip|xochip] <path to rom>";
+
+fn main() {
+    let mut platform = None;
+    let mut rom_path = None;
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--platform" => {
+                let name = args.next().unwrap_or_else(|| fail(USAGE));
+                platform = Some(
+                    Platform::from_name(&name)
+                        .unwrap_or_else(|| fail(&format!("unknown platform: {}", name))),
+                );
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if rom_path.is_none() => rom_path = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+
+    let rom_path = rom_path.unwrap_or_else(|| fail(USAGE));
+    // like the emulator, the extension picks the platform unless told otherwise
+    let platform = platform.unwrap_or_else(|| Platform::from_path(Path::new(&rom_path)));
+    let rom = std::fs::read(&rom_path)
+        .unwrap_or_else(|err| fail(&format!("can't read {}: {}", rom_path, err)));
+
+    print!("{}", disasm::listing(&rom, platform));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1);
+}
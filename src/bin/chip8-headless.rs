@@ -0,0 +1,181 @@
+use chip_8::display::Palette;
+use chip_8::headless::{self, InputScript, Runner};
+use chip_8::movie::{Movie, Player};
+use chip_8::record::GifRecorder;
+use chip_8::{Cpu, MovieError, Platform, Quirks, Timing};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::process::exit;
+
+const USAGE: &str = "usage: chip8-headless [--platform chip8|schip|xochip] [--quirks <preset>] \
+[--frames <count>] [--ipf <instructions per frame>] [--timing ipf|vip] [--seed <number>] [--input <script>] \
+[--movie <path> [--verify]] [--png <path>] [--pbm <path>] [--gif <path>] [--scale <factor>] [--state <path>] <path to rom>";
+
+fn main() {
+    let mut platform = None;
+    let mut quirks = None;
+    let mut frames = None;
+    let mut ticks_per_frame = 10;
+    let mut timing = Timing::default();
+    let mut seed = None;
+    let mut script = InputScript::default();
+    let mut movie = None;
+    let mut verify = false;
+    let mut png_path = None;
+    let mut pbm_path = None;
+    let mut gif_path = None;
+    let mut scale = 1;
+    let mut state_path = None;
+    let mut rom_path = None;
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| fail(USAGE));
+
+        match arg.as_str() {
+            "--platform" => {
+                let name = value();
+                platform = Some(
+                    Platform::from_name(&name)
+                        .unwrap_or_else(|| fail(&format!("unknown platform: {}", name))),
+                );
+            }
+            "--quirks" => {
+                let name = value();
+                quirks = Some(
+                    Quirks::from_name(&name)
+                        .unwrap_or_else(|| fail(&format!("unknown quirks preset: {}", name))),
+                );
+            }
+            "--frames" => frames = Some(parse_number(&value())),
+            "--ipf" => {
+                ticks_per_frame = match parse_number(&value()) {
+                    0 => fail("the instructions per frame must be at least 1"),
+                    ticks => ticks,
+                }
+            }
+            "--timing" => {
+                let name = value();
+                timing = Timing::from_name(&name)
+                    .unwrap_or_else(|| fail(&format!("unknown timing: {}", name)));
+            }
+            "--seed" => seed = Some(parse_number(&value())),
+            "--input" => {
+                let path = value();
+                let text = std::fs::read_to_string(&path)
+                    .unwrap_or_else(|err| fail(&format!("can't read {}: {}", path, err)));
+                script = InputScript::parse(&text)
+                    .unwrap_or_else(|err| fail(&format!("{}: {}", path, err)));
+            }
+            "--movie" => {
+                let path = value();
+                movie = Some(
+                    Movie::load(Path::new(&path))
+                        .unwrap_or_else(|err| fail(&format!("{}: {}", path, err))),
+                );
+            }
+            "--verify" => verify = true,
+            "--png" => png_path = Some(value()),
+            "--pbm" => pbm_path = Some(value()),
+            "--gif" => gif_path = Some(value()),
+            "--scale" => {
+                scale = match parse_number(&value()) {
+                    0 => fail("the scale must be at least 1"),
+                    scale => scale,
+                }
+            }
+            "--state" => state_path = Some(value()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if rom_path.is_none() && !arg.starts_with("--") => rom_path = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+
+    let rom_path = rom_path.unwrap_or_else(|| fail(USAGE));
+    let platform = platform.unwrap_or_else(|| Platform::from_path(Path::new(&rom_path)));
+    let quirks = quirks.unwrap_or_else(|| platform.quirks());
+    // a movie brings its own platform, quirks and seed. Without a seed the
+    // run isn't repeatable, like in the emulator
+    let mut cpu = match (&movie, seed) {
+        (Some(movie), _) => movie.cpu(),
+        (None, Some(seed)) => Cpu::with_seed(platform, quirks, seed),
+        (None, None) => Cpu::new(platform, quirks),
+    };
+    cpu.set_timing(timing);
+    cpu.load_rom(Path::new(&rom_path))
+        .unwrap_or_else(|err| fail(&format!("can't load {}: {}", rom_path, err)));
+
+    let mut recorder = gif_path.as_ref().map(|path| {
+        GifRecorder::new(create(path), &Palette::default(), scale)
+            .unwrap_or_else(|err| fail(&format!("can't write {}: {}", path, err)))
+    });
+    let frames =
+        frames.unwrap_or_else(|| movie.as_ref().map_or(60, |movie| movie.frames.len() as u64));
+    let mut runner = Runner::new(ticks_per_frame, script);
+    let mut player = movie.map(|movie| Player::new(movie, verify));
+    let mut frame = 0;
+    let mut result = Ok(());
+
+    while frame < frames && !cpu.is_halted() && result.is_ok() {
+        result = match player.as_mut() {
+            Some(player) => player.run_frame(&mut cpu),
+            None => runner.run_frame(&mut cpu).map_err(MovieError::from),
+        };
+        frame += 1;
+
+        if let Some(recorder) = recorder.as_mut() {
+            recorder
+                .record_frame(cpu.display())
+                .unwrap_or_else(|err| fail(&format!("can't record: {}", err)));
+        }
+    }
+
+    // the outputs are still written after a crash, they help to find out why
+    if let Some(path) = png_path {
+        let file = create(&path);
+        cpu.display()
+            .write_png(file, &Palette::default(), scale)
+            .unwrap_or_else(|err| fail(&format!("can't write {}: {}", path, err)));
+    }
+    if let Some(path) = pbm_path {
+        let mut file = create(&path);
+        cpu.display()
+            .write_pbm(&mut file)
+            .unwrap_or_else(|err| fail(&format!("can't write {}: {}", path, err)));
+    }
+    if let Some(recorder) = recorder {
+        recorder
+            .finish()
+            .unwrap_or_else(|err| fail(&format!("can't write {}: {}", gif_path.unwrap(), err)));
+    }
+    if let Some(path) = state_path {
+        std::fs::write(&path, headless::state_json(&cpu, frame))
+            .unwrap_or_else(|err| fail(&format!("can't write {}: {}", path, err)));
+    }
+
+    match result {
+        Err(MovieError::Cpu(err)) => fail(&format!("crashed on frame {}: {}", frame - 1, err)),
+        Err(err) => fail(&err.to_string()),
+        Ok(()) => (),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> T {
+    text.parse()
+        .unwrap_or_else(|_| fail(&format!("invalid number: {}", text)))
+}
+
+fn create(path: &str) -> BufWriter<File> {
+    File::create(path)
+        .map(BufWriter::new)
+        .unwrap_or_else(|err| fail(&format!("can't create {}: {}", path, err)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1);
+}
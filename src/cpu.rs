@@ -1,57 +1,171 @@
-use crate::font::FONT_ADDRESS;
-use crate::mem::Mem;
-use rand::{rngs::ThreadRng, Rng};
-use std::{io::Error, path::Path};
+use crate::display::{Display, PLANE_COUNT};
+use crate::error::CpuError;
+use crate::font::{BIG_FONT_ADDRESS, FONT_ADDRESS};
+use crate::mem::{Mem, OutOfBounds};
+use crate::platform::Platform;
+use crate::quirks::{MemoryIncrement, Quirks};
+use crate::rng::Rng;
+pub use debug::{Break, Registers};
+use std::{
+    collections::BTreeSet,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+pub use timing::{Timing, VIP_FRAME_CYCLES};
 
 pub const OPCODE_SIZE: u16 = 2;
 pub const START_ADDRESS: u16 = 0x200;
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
+pub const HIRES_SCREEN_WIDTH: usize = 128;
+pub const HIRES_SCREEN_HEIGHT: usize = 64;
+pub const AUDIO_PATTERN_SIZE: usize = 16;
+pub const DEFAULT_PITCH: u8 = 64;
+pub const STACK_SIZE: usize = 16;
+// unknown opcodes kept around by UnknownOpcodePolicy::Log until taken
+pub const MAX_LOGGED_OPCODES: usize = 256;
+
+/// What [`Cpu::tick`] does when it meets an opcode the platform doesn't have.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownOpcodePolicy {
+    /// skip it as if it were a no-op
+    Ignore,
+    /// skip it and keep it for [`Cpu::take_unknown_opcodes`]
+    Log,
+    /// return a [`CpuError::UnknownOpcode`]
+    #[default]
+    Halt,
+}
+
+impl UnknownOpcodePolicy {
+    pub fn from_name(name: &str) -> Option<UnknownOpcodePolicy> {
+        match name {
+            "ignore" => Some(UnknownOpcodePolicy::Ignore),
+            "log" => Some(UnknownOpcodePolicy::Log),
+            "halt" => Some(UnknownOpcodePolicy::Halt),
+            _ => None,
+        }
+    }
+}
 
 pub struct Cpu {
     pc: u16,
     ram: Mem,
-    stack: [u16; 16],
+    stack: [u16; STACK_SIZE],
     stack_pointer: u8,
     i_reg: u16,
     v_reg: [u8; 16],
     delay_timer: u8,
     sound_timer: u8,
-    display: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    display: Display,
     keypad: [bool; 16],
+    // keys released while FX0A waits, one bit per key
+    released_keys: u16,
+    // keys released since the front end last asked, for input movies
+    key_releases: u16,
+    waiting_for_key: bool,
+    rpl_flags: [u8; 16],
+    planes: u8,
+    audio_pattern: [u8; AUDIO_PATTERN_SIZE],
+    pitch: u8,
+    halted: bool,
     pub draw_flag: bool,
-    rng: ThreadRng,
+    // a sprite was drawn with the display wait quirk, the frame is over
+    frame_ended: bool,
+    // instructions run since the machine was created
+    instructions: u64,
+    timing: Timing,
+    // machine cycles left in the frame, below 0 when the last instruction
+    // went over and the next frame gets fewer
+    cycles: i64,
+    rng: Rng,
+    seed: u64,
+    platform: Platform,
+    quirks: Quirks,
+    unknown_opcode_policy: UnknownOpcodePolicy,
+    unknown_opcodes: Vec<CpuError>,
+    rom_hash: [u8; 20],
+    breakpoints: BTreeSet<u16>,
+    // breakpoint run passes over when starting
+    stopped_at: Option<u16>,
 }
 
 impl Cpu {
-    pub fn new() -> Self {
-        let mut ram = Mem::new();
+    pub fn new(platform: Platform, quirks: Quirks) -> Self {
+        Self::with_seed(platform, quirks, rand::random())
+    }
+
+    // the same seed gives the same CXNN results
+    pub fn with_seed(platform: Platform, quirks: Quirks, seed: u64) -> Self {
+        let mut ram = Mem::new(platform.memory_size());
         ram.load_font();
 
         Self {
             pc: START_ADDRESS,
             ram,
-            stack: [0; 16],
+            stack: [0; STACK_SIZE],
             stack_pointer: 0,
             i_reg: 0,
             v_reg: [0; 16],
             delay_timer: 0,
             sound_timer: 0,
-            display: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            display: Display::new(SCREEN_WIDTH, SCREEN_HEIGHT),
             keypad: [false; 16],
+            released_keys: 0,
+            key_releases: 0,
+            waiting_for_key: false,
+            rpl_flags: [0; 16],
+            planes: 0b01,
+            audio_pattern: [0; AUDIO_PATTERN_SIZE],
+            pitch: DEFAULT_PITCH,
+            halted: false,
             draw_flag: true,
-            rng: rand::thread_rng(),
+            frame_ended: false,
+            instructions: 0,
+            timing: Timing::default(),
+            cycles: 0,
+            rng: Rng::new(seed),
+            seed,
+            platform,
+            quirks,
+            unknown_opcode_policy: UnknownOpcodePolicy::default(),
+            unknown_opcodes: Vec::new(),
+            rom_hash: [0; 20],
+            breakpoints: BTreeSet::new(),
+            stopped_at: None,
+        }
+    }
+
+    pub fn tick(&mut self) -> Result<(), CpuError> {
+        if self.halted {
+            return Ok(());
+        }
+
+        let pc = self.pc;
+        let opcode = self.fetch().map_err(|fault| fault.at(pc, 0))?;
+        self.instructions += 1;
+        if self.timing == Timing::CosmacVip {
+            self.cycles -= self.cycle_cost(opcode);
+        }
+
+        match self.process(opcode) {
+            Err(Fault::UnknownOpcode) => self.unknown_opcode(pc, opcode),
+            result => result.map_err(|fault| fault.at(pc, opcode)),
         }
     }
 
-    pub fn tick(&mut self) {
-        let opcode = self.fetch();
-        self.process(opcode);
+    pub fn set_unknown_opcode_policy(&mut self, policy: UnknownOpcodePolicy) {
+        self.unknown_opcode_policy = policy;
+    }
+
+    // the unknown opcodes met since the last call, oldest first
+    pub fn take_unknown_opcodes(&mut self) -> Vec<CpuError> {
+        std::mem::take(&mut self.unknown_opcodes)
     }
 
     pub fn reset(&mut self) {
         self.pc = START_ADDRESS;
-        self.ram = Mem::new();
+        self.ram.restore(&vec![0; self.ram.size()]);
         self.ram.load_font();
         self.stack_pointer = 0;
         self.i_reg = 0;
@@ -59,13 +173,38 @@ impl Cpu {
         self.delay_timer = 0;
         self.sound_timer = 0;
         self.keypad = [false; 16];
+        self.released_keys = 0;
+        self.key_releases = 0;
+        self.waiting_for_key = false;
+        self.cycles = 0;
+        self.planes = 0b01;
+        self.audio_pattern = [0; AUDIO_PATTERN_SIZE];
+        self.pitch = DEFAULT_PITCH;
+        self.halted = false;
+        self.rng = Rng::new(self.seed);
+        self.stopped_at = None;
+        self.display.resize(SCREEN_WIDTH, SCREEN_HEIGHT);
         self.clear_display();
     }
 
     pub fn load_rom(&mut self, path: &Path) -> Result<(), Error> {
         let program = std::fs::read(path)?;
 
-        self.ram.load_program(&program);
+        self.load_program(&program)
+    }
+
+    pub fn load_program(&mut self, program: &[u8]) -> Result<(), Error> {
+        self.ram.load_program(program).map_err(|_| {
+            let available = self.ram.size() - START_ADDRESS as usize;
+            let message = format!(
+                "rom is {} bytes but only {} bytes are available",
+                program.len(),
+                available
+            );
+
+            Error::new(ErrorKind::InvalidData, message)
+        })?;
+        self.rom_hash = sha1_smol::Sha1::from(program).digest().bytes();
 
         Ok(())
     }
@@ -80,45 +219,188 @@ impl Cpu {
         }
     }
 
-    pub fn display(&self) -> &[bool] {
+    /// Runs the instructions of one frame and then the timers, see
+    /// [`Cpu::run`] for when it stops early. A breakpoint or watchpoint ends
+    /// the frame without the timers.
+    ///
+    /// A frame is `ticks_per_frame` instructions, or with
+    /// [`Timing::CosmacVip`] as many machine cycles as the VIP had.
+    pub fn run_frame(&mut self, ticks_per_frame: usize) -> Result<Option<Break>, CpuError> {
+        let stop = match self.timing {
+            Timing::Instructions => self.run(ticks_per_frame)?,
+            Timing::CosmacVip => {
+                // cycles left over from a frame cut short aren't saved up
+                self.cycles = self.cycles.min(0) + VIP_FRAME_CYCLES;
+                self.run_ticks(usize::MAX, true)?
+            }
+        };
+
+        if stop.is_none() {
+            self.tick_timers();
+        }
+
+        Ok(stop)
+    }
+
+    // for counting instructions per frame or second
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    pub fn display(&self) -> &Display {
         &self.display
     }
 
+    pub fn memory(&self) -> &Mem {
+        &self.ram
+    }
+
+    // for debuggers, watchpoints and traces are set up on the memory
+    pub fn memory_mut(&mut self) -> &mut Mem {
+        &mut self.ram
+    }
+
+    pub fn keypad(&self) -> &[bool; 16] {
+        &self.keypad
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    // SHA-1 of the loaded rom
+    pub fn rom_hash(&self) -> [u8; 20] {
+        self.rom_hash
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn is_beeping(&self) -> bool {
+        self.sound_timer > 0
+    }
+
+    pub fn audio_pattern(&self) -> &[u8; AUDIO_PATTERN_SIZE] {
+        &self.audio_pattern
+    }
+
+    // bits per second the audio pattern is played at
+    pub fn playback_rate(&self) -> f32 {
+        4000.0 * 2f32.powf((self.pitch as f32 - 64.0) / 48.0)
+    }
+
+    // keys past `F` don't exist and are ignored
     pub fn key_press(&mut self, key: usize) {
-        self.keypad[key] = true;
+        if let Some(down) = self.keypad.get_mut(key) {
+            *down = true;
+        }
     }
 
     pub fn key_release(&mut self, key: usize) {
-        self.keypad[key] = false;
+        if let Some(down) = self.keypad.get_mut(key) {
+            if *down {
+                self.released_keys |= 1 << key;
+                self.key_releases |= 1 << key;
+            }
+            *down = false;
+        }
+    }
+
+    // the keys released since the last call, one bit per key
+    pub fn take_key_releases(&mut self) -> u16 {
+        std::mem::take(&mut self.key_releases)
     }
 
-    fn fetch(&mut self) -> u16 {
-        let op = self.ram.read_opcode(self.pc);
-        self.pc += OPCODE_SIZE;
+    // FX0A stopped the program until a key comes, the timers keep running
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.waiting_for_key
+    }
+
+    fn fetch(&mut self) -> Result<u16, Fault> {
+        let op = self.ram.fetch(self.pc)?;
+        self.pc = self.pc.wrapping_add(OPCODE_SIZE);
 
-        op
+        Ok(op)
     }
 
     fn skip_next_instruction(&mut self) {
-        self.pc += OPCODE_SIZE;
+        if self.timing == Timing::CosmacVip {
+            self.cycles -= timing::SKIP_CYCLES;
+        }
+
+        // F000 NNNN is the only instruction twice as long
+        if self.platform.has_xo_opcodes() && self.ram.peek_opcode(self.pc) == Ok(0xF000) {
+            self.pc = self.pc.wrapping_add(OPCODE_SIZE);
+        }
+
+        self.pc = self.pc.wrapping_add(OPCODE_SIZE);
     }
 
     pub fn clear_display(&mut self) {
         self.op_00e0();
     }
 
+    // SCD nibble
+    fn op_00cn(&mut self, nibble: usize) {
+        self.display.scroll_down(nibble, self.planes);
+        self.draw_flag = true;
+    }
+
+    // SCU nibble
+    fn op_00dn(&mut self, nibble: usize) {
+        self.display.scroll_up(nibble, self.planes);
+        self.draw_flag = true;
+    }
+
     // CLS
     fn op_00e0(&mut self) {
-        for pixel in self.display.iter_mut() {
-            *pixel = false;
-        }
-
+        self.display.clear(self.planes);
         self.draw_flag = true;
     }
 
     // RET
-    fn op_00ee(&mut self) {
-        self.pc = self.stack_pop();
+    fn op_00ee(&mut self) -> Result<(), Fault> {
+        self.pc = self.stack_pop()?;
+
+        Ok(())
+    }
+
+    // SCR
+    fn op_00fb(&mut self) {
+        self.display.scroll_right(4, self.planes);
+        self.draw_flag = true;
+    }
+
+    // SCL
+    fn op_00fc(&mut self) {
+        self.display.scroll_left(4, self.planes);
+        self.draw_flag = true;
+    }
+
+    // EXIT
+    fn op_00fd(&mut self) {
+        self.halted = true;
+    }
+
+    // LOW
+    fn op_00fe(&mut self) {
+        self.display.resize(SCREEN_WIDTH, SCREEN_HEIGHT);
+        self.draw_flag = true;
+    }
+
+    // HIGH
+    fn op_00ff(&mut self) {
+        self.display.resize(HIRES_SCREEN_WIDTH, HIRES_SCREEN_HEIGHT);
+        self.draw_flag = true;
     }
 
     // JP addr
@@ -127,9 +409,11 @@ impl Cpu {
     }
 
     // CALL addr
-    fn op_2nnn(&mut self, addr: u16) {
-        self.stack_push(self.pc);
+    fn op_2nnn(&mut self, addr: u16) -> Result<(), Fault> {
+        self.stack_push(self.pc)?;
         self.pc = addr;
+
+        Ok(())
     }
 
     // SE Vx, byte
@@ -153,6 +437,29 @@ impl Cpu {
         };
     }
 
+    // SAVE Vx - Vy
+    fn op_5xy2(&mut self, x: usize, y: usize) -> Result<(), Fault> {
+        let registers: Vec<u8> = register_range(x, y)
+            .into_iter()
+            .map(|i| self.v_reg[i])
+            .collect();
+
+        self.ram.write(self.i_reg, &registers)?;
+
+        Ok(())
+    }
+
+    // LOAD Vx - Vy
+    fn op_5xy3(&mut self, x: usize, y: usize) -> Result<(), Fault> {
+        let src = self.ram.read(self.i_reg, x.abs_diff(y) + 1)?;
+
+        for (value, i) in src.iter().zip(register_range(x, y)) {
+            self.v_reg[i] = *value;
+        }
+
+        Ok(())
+    }
+
     // LD Vx, byte
     fn op_6xnn(&mut self, x: usize, byte: u8) {
         self.v_reg[x] = byte;
@@ -171,16 +478,19 @@ impl Cpu {
     // OR Vx, Vy
     fn op_8xy1(&mut self, x: usize, y: usize) {
         self.v_reg[x] |= self.v_reg[y];
+        self.reset_vf_after_logic();
     }
 
     // AND Vx, Vy
     fn op_8xy2(&mut self, x: usize, y: usize) {
         self.v_reg[x] &= self.v_reg[y];
+        self.reset_vf_after_logic();
     }
 
     // XOR Vx, Vy
     fn op_8xy3(&mut self, x: usize, y: usize) {
         self.v_reg[x] ^= self.v_reg[y];
+        self.reset_vf_after_logic();
     }
 
     // ADD Vx, Vy
@@ -203,12 +513,12 @@ impl Cpu {
         self.v_reg[x] = self.v_reg[x].wrapping_sub(self.v_reg[y]);
     }
 
-    // SHR Vx, _Vy
-    fn op_8xy6(&mut self, x: usize, _y: usize) {
-        // self.v_reg[x] = self.v_reg[_y];
-        self.v_reg[0xF] = self.v_reg[x] & 0b0000_0001;
+    // SHR Vx, Vy
+    fn op_8xy6(&mut self, x: usize, y: usize) {
+        let value = self.shift_source(x, y);
 
-        self.v_reg[x] >>= 1;
+        self.v_reg[x] = value >> 1;
+        self.v_reg[0xF] = value & 0b0000_0001;
     }
 
     // SUBN Vx, Vy
@@ -217,12 +527,12 @@ impl Cpu {
         self.v_reg[x] = self.v_reg[y].wrapping_sub(self.v_reg[x]);
     }
 
-    // SHL Vx, _Vy
-    fn op_8xye(&mut self, x: usize, _y: usize) {
-        // self.v_reg[x] = self.v_reg[_y];
-        self.v_reg[0xF] = (self.v_reg[x] & 0b1000_0000) >> 7;
+    // SHL Vx, Vy
+    fn op_8xye(&mut self, x: usize, y: usize) {
+        let value = self.shift_source(x, y);
 
-        self.v_reg[x] <<= 1;
+        self.v_reg[x] = value << 1;
+        self.v_reg[0xF] = (value & 0b1000_0000) >> 7;
     }
 
     // SNE Vx, Vy
@@ -238,63 +548,116 @@ impl Cpu {
     }
 
     // JP V0, addr
-    fn op_bnnn(&mut self, addr: u16) {
-        self.pc = addr + self.v_reg[0x0] as u16;
+    fn op_bnnn(&mut self, x: usize, addr: u16) {
+        // CHIP-48 reads the high nibble of the address as a register too
+        let offset = if self.quirks.jump_uses_vx {
+            self.v_reg[x]
+        } else {
+            self.v_reg[0x0]
+        };
+
+        self.pc = addr + offset as u16;
     }
 
     // RND Vx, byte
     fn op_cxnn(&mut self, x: usize, byte: u8) {
-        let random = self.rng.gen::<u8>();
+        let random = self.rng.next_u8();
 
         self.v_reg[x] = random & byte;
     }
 
     // DRW Vx, Vy, nibble
-    fn op_dxyn(&mut self, x: usize, y: usize, nibble: usize) {
-        let offset_x = self.v_reg[x] as usize % SCREEN_WIDTH;
-        let offset_y = self.v_reg[y] as usize % SCREEN_HEIGHT;
+    fn op_dxyn(&mut self, x: usize, y: usize, nibble: usize) -> Result<(), Fault> {
+        let width = self.display.width();
+        let height = self.display.height();
+        let offset_x = self.v_reg[x] as usize % width;
+        let offset_y = self.v_reg[y] as usize % height;
+
+        // DXY0 draws a 16x16 sprite on SUPER-CHIP, SUPER-CHIP 1.1 draws 8x16
+        // in low resolution while XO-CHIP keeps it 16x16
+        let low_resolution = width == SCREEN_WIDTH;
+        let (sprite_width, rows) = match nibble {
+            0 if self.platform == Platform::SuperChip && low_resolution => (8, 16),
+            0 if self.platform.has_schip_opcodes() => (16, 16),
+            _ => (8, nibble),
+        };
+        let row_size = sprite_width / 8;
+        let sprite_size = rows * row_size;
 
         self.v_reg[0xF] = 0;
 
-        let sprite = self.ram.read(self.i_reg, nibble);
-        for (i, pixel_row) in sprite.iter().enumerate() {
-            if offset_y + i >= SCREEN_HEIGHT {
-                break;
+        // XO-CHIP stores the sprite of every selected plane one after another
+        let mut addr = self.i_reg;
+        for plane in (0..PLANE_COUNT).map(|i| 1 << i) {
+            if self.planes & plane == 0 {
+                continue;
             }
 
-            for j in 0..8 {
-                if offset_x + j >= SCREEN_WIDTH {
+            let sprite = self.ram.read(addr, sprite_size)?;
+            for (i, pixel_row) in sprite.chunks(row_size).enumerate() {
+                if self.quirks.clip_sprites && offset_y + i >= height {
                     break;
                 }
 
-                let pixel = pixel_row & (0b1000_0000 >> j) != 0;
-                let pos = (offset_y + i) * SCREEN_WIDTH + (offset_x + j);
+                for j in 0..sprite_width {
+                    if self.quirks.clip_sprites && offset_x + j >= width {
+                        break;
+                    }
 
-                if self.display[pos] && pixel {
-                    self.v_reg[0xF] = 1;
-                }
+                    let pixel = pixel_row[j / 8] & (0b1000_0000 >> (j % 8)) != 0;
+                    let pos_x = (offset_x + j) % width;
+                    let pos_y = (offset_y + i) % height;
 
-                self.display[pos] ^= pixel;
+                    if pixel && self.display.flip(pos_x, pos_y, plane) {
+                        self.v_reg[0xF] = 1;
+                    }
+                }
             }
+
+            addr = addr.wrapping_add(sprite_size as u16);
         }
 
         self.draw_flag = true;
+        self.frame_ended = self.quirks.display_wait;
+
+        Ok(())
     }
 
     // SKP Vx
     fn op_ex9e(&mut self, x: usize) {
-        if self.keypad[self.v_reg[x] as usize] {
+        if self.keypad[self.v_reg[x] as usize & 0xF] {
             self.skip_next_instruction();
         }
     }
 
     // SKNP Vx
     fn op_exa1(&mut self, x: usize) {
-        if !self.keypad[self.v_reg[x] as usize] {
+        if !self.keypad[self.v_reg[x] as usize & 0xF] {
             self.skip_next_instruction();
         }
     }
 
+    // LD I, long addr
+    fn op_f000(&mut self) -> Result<(), Fault> {
+        self.i_reg = self.ram.read_opcode(self.pc)?;
+        self.pc = self.pc.wrapping_add(OPCODE_SIZE);
+
+        Ok(())
+    }
+
+    // PLANE n
+    fn op_fn01(&mut self, n: usize) {
+        self.planes = n as u8 & 0b11;
+    }
+
+    // AUDIO
+    fn op_f002(&mut self) -> Result<(), Fault> {
+        let src = self.ram.read(self.i_reg, AUDIO_PATTERN_SIZE)?;
+        self.audio_pattern.copy_from_slice(src);
+
+        Ok(())
+    }
+
     // LD Vx, DT
     fn op_fx07(&mut self, x: usize) {
         self.v_reg[x] = self.delay_timer;
@@ -302,9 +665,26 @@ impl Cpu {
 
     // LD Vx, K
     fn op_fx0a(&mut self, x: usize) {
-        match self.keypad.iter().position(|key| *key) {
-            Some(key_index) => self.v_reg[x] = key_index as u8,
-            None => self.pc -= OPCODE_SIZE,
+        // only keys released after the wait started count
+        if !self.waiting_for_key {
+            self.released_keys = 0;
+        }
+
+        let key = if self.quirks.wait_for_release {
+            (0..16).find(|key| self.released_keys & 1 << key != 0)
+        } else {
+            self.keypad.iter().position(|key| *key)
+        };
+
+        match key {
+            Some(key_index) => {
+                self.v_reg[x] = key_index as u8;
+                self.waiting_for_key = false;
+            }
+            None => {
+                self.pc = self.pc.wrapping_sub(OPCODE_SIZE);
+                self.waiting_for_key = true;
+            }
         }
     }
 
@@ -320,7 +700,7 @@ impl Cpu {
 
     // ADD I, Vx
     fn op_fx1e(&mut self, x: usize) {
-        self.i_reg += self.v_reg[x] as u16;
+        self.i_reg = self.i_reg.wrapping_add(self.v_reg[x] as u16);
     }
 
     // LD F, Vx
@@ -330,8 +710,19 @@ impl Cpu {
         self.i_reg = FONT_ADDRESS + ((self.v_reg[x] & 0xF) * 5) as u16;
     }
 
+    // LD HF, Vx
+    fn op_fx30(&mut self, x: usize) {
+        // each big sprite is 10 byte long
+        self.i_reg = BIG_FONT_ADDRESS + ((self.v_reg[x] & 0xF) as u16 * 10);
+    }
+
+    // PITCH Vx
+    fn op_fx3a(&mut self, x: usize) {
+        self.pitch = self.v_reg[x];
+    }
+
     // LD B, Vx
-    fn op_fx33(&mut self, x: usize) {
+    fn op_fx33(&mut self, x: usize) -> Result<(), Fault> {
         let num = self.v_reg[x];
 
         // BCD Conversion
@@ -339,21 +730,39 @@ impl Cpu {
         let byte2 = (num / 10) % 10;
         let byte3 = num % 10;
 
-        self.ram.write(self.i_reg, &[byte1, byte2, byte3]);
+        self.ram.write(self.i_reg, &[byte1, byte2, byte3])?;
+
+        Ok(())
     }
 
     // LD [I], Vx
-    fn op_fx55(&mut self, x: usize) {
-        self.ram.write(self.i_reg, &self.v_reg[0..=x]);
+    fn op_fx55(&mut self, x: usize) -> Result<(), Fault> {
+        self.ram.write(self.i_reg, &self.v_reg[0..=x])?;
+        self.increment_i_after_memory(x);
+
+        Ok(())
     }
 
     // LD Vx, [I]
-    fn op_fx65(&mut self, x: usize) {
-        let src = self.ram.read(self.i_reg, x + 1);
+    fn op_fx65(&mut self, x: usize) -> Result<(), Fault> {
+        let src = self.ram.read(self.i_reg, x + 1)?;
         self.v_reg[0..=x].copy_from_slice(src);
+        self.increment_i_after_memory(x);
+
+        Ok(())
+    }
+
+    // LD R, Vx
+    fn op_fx75(&mut self, x: usize) {
+        self.rpl_flags[0..=x].copy_from_slice(&self.v_reg[0..=x]);
     }
 
-    fn process(&mut self, opcode: u16) {
+    // LD Vx, R
+    fn op_fx85(&mut self, x: usize) {
+        self.v_reg[0..=x].copy_from_slice(&self.rpl_flags[0..=x]);
+    }
+
+    fn process(&mut self, opcode: u16) -> Result<(), Fault> {
         let nibbles = extract_nibbles(opcode);
 
         // param extraction
@@ -362,15 +771,26 @@ impl Cpu {
         let n = nibbles.3 as usize;
         let nn = (opcode & 0xFF) as u8;
         let nnn = opcode & 0xFFF;
+        let schip = self.platform.has_schip_opcodes();
+        let xo = self.platform.has_xo_opcodes();
 
         match nibbles {
+            (0x0, 0x0, 0xC, _) if schip => self.op_00cn(n),
+            (0x0, 0x0, 0xD, _) if xo => self.op_00dn(n),
             (0x0, 0x0, 0xE, 0x0) => self.op_00e0(),
-            (0x0, 0x0, 0xE, 0xE) => self.op_00ee(),
+            (0x0, 0x0, 0xE, 0xE) => self.op_00ee()?,
+            (0x0, 0x0, 0xF, 0xB) if schip => self.op_00fb(),
+            (0x0, 0x0, 0xF, 0xC) if schip => self.op_00fc(),
+            (0x0, 0x0, 0xF, 0xD) if schip => self.op_00fd(),
+            (0x0, 0x0, 0xF, 0xE) if schip => self.op_00fe(),
+            (0x0, 0x0, 0xF, 0xF) if schip => self.op_00ff(),
             (0x1, _, _, _) => self.op_1nnn(nnn),
-            (0x2, _, _, _) => self.op_2nnn(nnn),
+            (0x2, _, _, _) => self.op_2nnn(nnn)?,
             (0x3, _, _, _) => self.op_3xnn(x, nn),
             (0x4, _, _, _) => self.op_4xnn(x, nn),
             (0x5, _, _, 0x0) => self.op_5xy0(x, y),
+            (0x5, _, _, 0x2) if xo => self.op_5xy2(x, y)?,
+            (0x5, _, _, 0x3) if xo => self.op_5xy3(x, y)?,
             (0x6, _, _, _) => self.op_6xnn(x, nn),
             (0x7, _, _, _) => self.op_7xnn(x, nn),
             (0x8, _, _, 0x0) => self.op_8xy0(x, y),
@@ -384,36 +804,128 @@ impl Cpu {
             (0x8, _, _, 0xE) => self.op_8xye(x, y),
             (0x9, _, _, 0x0) => self.op_9xy0(x, y),
             (0xA, _, _, _) => self.op_annn(nnn),
-            (0xB, _, _, _) => self.op_bnnn(nnn),
+            (0xB, _, _, _) => self.op_bnnn(x, nnn),
             (0xC, _, _, _) => self.op_cxnn(x, nn),
-            (0xD, _, _, _) => self.op_dxyn(x, y, n),
+            (0xD, _, _, _) => self.op_dxyn(x, y, n)?,
             (0xE, _, 0x9, 0xE) => self.op_ex9e(x),
             (0xE, _, 0xA, 0x1) => self.op_exa1(x),
+            (0xF, 0x0, 0x0, 0x0) if xo => self.op_f000()?,
+            (0xF, _, 0x0, 0x1) if xo => self.op_fn01(x),
+            (0xF, 0x0, 0x0, 0x2) if xo => self.op_f002()?,
             (0xF, _, 0x0, 0x7) => self.op_fx07(x),
             (0xF, _, 0x0, 0xA) => self.op_fx0a(x),
             (0xF, _, 0x1, 0x5) => self.op_fx15(x),
             (0xF, _, 0x1, 0x8) => self.op_fx18(x),
             (0xF, _, 0x1, 0xE) => self.op_fx1e(x),
             (0xF, _, 0x2, 0x9) => self.op_fx29(x),
-            (0xF, _, 0x3, 0x3) => self.op_fx33(x),
-            (0xF, _, 0x5, 0x5) => self.op_fx55(x),
-            (0xF, _, 0x6, 0x5) => self.op_fx65(x),
-            _ => (),
+            (0xF, _, 0x3, 0x0) if schip => self.op_fx30(x),
+            (0xF, _, 0x3, 0x3) => self.op_fx33(x)?,
+            (0xF, _, 0x3, 0xA) if xo => self.op_fx3a(x),
+            (0xF, _, 0x5, 0x5) => self.op_fx55(x)?,
+            (0xF, _, 0x6, 0x5) => self.op_fx65(x)?,
+            (0xF, _, 0x7, 0x5) if schip => self.op_fx75(x),
+            (0xF, _, 0x8, 0x5) if schip => self.op_fx85(x),
+            _ => return Err(Fault::UnknownOpcode),
+        }
+
+        Ok(())
+    }
+
+    fn unknown_opcode(&mut self, pc: u16, opcode: u16) -> Result<(), CpuError> {
+        let error = Fault::UnknownOpcode.at(pc, opcode);
+
+        match self.unknown_opcode_policy {
+            UnknownOpcodePolicy::Ignore => Ok(()),
+            UnknownOpcodePolicy::Log => {
+                if self.unknown_opcodes.len() < MAX_LOGGED_OPCODES {
+                    self.unknown_opcodes.push(error);
+                }
+
+                Ok(())
+            }
+            UnknownOpcodePolicy::Halt => Err(error),
+        }
+    }
+
+    fn shift_source(&self, x: usize, y: usize) -> u8 {
+        if self.quirks.shift_uses_vy {
+            self.v_reg[y]
+        } else {
+            self.v_reg[x]
         }
     }
 
-    fn stack_push(&mut self, value: u16) {
+    fn reset_vf_after_logic(&mut self) {
+        if self.quirks.logic_resets_vf {
+            self.v_reg[0xF] = 0;
+        }
+    }
+
+    fn increment_i_after_memory(&mut self, x: usize) {
+        match self.quirks.memory_increment {
+            MemoryIncrement::Unchanged => (),
+            MemoryIncrement::ByX => self.i_reg = self.i_reg.wrapping_add(x as u16),
+            MemoryIncrement::ByXPlusOne => self.i_reg = self.i_reg.wrapping_add(x as u16 + 1),
+        }
+    }
+
+    fn stack_push(&mut self, value: u16) -> Result<(), Fault> {
+        if self.stack_pointer as usize == STACK_SIZE {
+            return Err(Fault::StackOverflow);
+        }
+
         self.stack[self.stack_pointer as usize] = value;
         self.stack_pointer += 1;
+
+        Ok(())
     }
 
-    fn stack_pop(&mut self) -> u16 {
+    fn stack_pop(&mut self) -> Result<u16, Fault> {
+        if self.stack_pointer == 0 {
+            return Err(Fault::StackUnderflow);
+        }
+
         self.stack_pointer -= 1;
-        self.stack[self.stack_pointer as usize]
+
+        Ok(self.stack[self.stack_pointer as usize])
     }
 }
 
-fn extract_nibbles(word: u16) -> (u8, u8, u8, u8) {
+// what went wrong inside an instruction, tick adds where it happened
+enum Fault {
+    StackOverflow,
+    StackUnderflow,
+    MemoryOutOfBounds(usize),
+    UnknownOpcode,
+}
+
+impl Fault {
+    fn at(self, pc: u16, opcode: u16) -> CpuError {
+        match self {
+            Fault::StackOverflow => CpuError::StackOverflow { pc, opcode },
+            Fault::StackUnderflow => CpuError::StackUnderflow { pc, opcode },
+            Fault::MemoryOutOfBounds(addr) => CpuError::MemoryOutOfBounds { pc, opcode, addr },
+            Fault::UnknownOpcode => CpuError::UnknownOpcode { pc, opcode },
+        }
+    }
+}
+
+impl From<OutOfBounds> for Fault {
+    fn from(error: OutOfBounds) -> Self {
+        Fault::MemoryOutOfBounds(error.addr)
+    }
+}
+
+// XO-CHIP register ranges can go in either direction
+fn register_range(x: usize, y: usize) -> Vec<usize> {
+    if x <= y {
+        (x..=y).collect()
+    } else {
+        (y..=x).rev().collect()
+    }
+}
+
+pub(crate) fn extract_nibbles(word: u16) -> (u8, u8, u8, u8) {
     let nibble_1 = ((word & 0xF000) >> 12) as u8;
     let nibble_2 = ((word & 0xF00) >> 8) as u8;
     let nibble_3 = ((word & 0xF0) >> 4) as u8;
@@ -422,5 +934,9 @@ fn extract_nibbles(word: u16) -> (u8, u8, u8, u8) {
     (nibble_1, nibble_2, nibble_3, nibble_4)
 }
 
+mod debug;
+mod state;
+mod timing;
+
 #[cfg(test)]
 mod tests;
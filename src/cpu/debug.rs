@@ -0,0 +1,108 @@
+use super::Cpu;
+use crate::error::CpuError;
+use crate::mem::Access;
+use std::collections::BTreeSet;
+
+/// Copy of the registers for debuggers, the stack holds only the pushed
+/// return addresses, oldest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub pc: u16,
+    pub i: u16,
+    pub sp: u8,
+    pub v: [u8; 16],
+    pub stack: Vec<u16>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+/// Why [`Cpu::run`] stopped before running all of its instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Break {
+    /// the instruction at this address is next
+    Breakpoint(u16),
+    /// the last instruction made this access to a watched address
+    Watchpoint(Access),
+}
+
+impl Cpu {
+    pub fn registers(&self) -> Registers {
+        Registers {
+            pc: self.pc,
+            i: self.i_reg,
+            sp: self.stack_pointer,
+            v: self.v_reg,
+            stack: self.stack[..self.stack_pointer as usize].to_vec(),
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        }
+    }
+
+    // returns false when there already was one
+    pub fn add_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.insert(addr)
+    }
+
+    // returns false when there was none
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<u16> {
+        &self.breakpoints
+    }
+
+    /// Runs up to `max_ticks` instructions, stopping in front of breakpoints.
+    ///
+    /// The breakpoint the machine stopped at, or was stepped to, is passed
+    /// over when running again. Watchpoints stop it after the instruction
+    /// that touched the memory and it also stops early on `00FD` and, with
+    /// the display wait quirk, after drawing a sprite.
+    pub fn run(&mut self, max_ticks: usize) -> Result<Option<Break>, CpuError> {
+        self.run_ticks(max_ticks, false)
+    }
+
+    // also stops once the frame's machine cycles are used up with `by_cycles`
+    pub(super) fn run_ticks(
+        &mut self,
+        max_ticks: usize,
+        by_cycles: bool,
+    ) -> Result<Option<Break>, CpuError> {
+        // hits from ticks outside of run are stale
+        self.ram.take_hit();
+        self.frame_ended = false;
+
+        for _ in 0..max_ticks {
+            if self.halted {
+                break;
+            }
+
+            if self.breakpoints.contains(&self.pc) && self.stopped_at != Some(self.pc) {
+                self.stopped_at = Some(self.pc);
+                return Ok(Some(Break::Breakpoint(self.pc)));
+            }
+
+            self.stopped_at = None;
+            self.tick()?;
+
+            // a breakpoint on the next instruction still stops the next run
+            if let Some(access) = self.ram.take_hit() {
+                return Ok(Some(Break::Watchpoint(access)));
+            }
+            if self.frame_ended || by_cycles && self.cycles <= 0 {
+                break;
+            }
+        }
+
+        Ok(None)
+    }
+
+    // one instruction, whether there is a breakpoint or not
+    pub fn step(&mut self) -> Result<(), CpuError> {
+        self.tick()?;
+        self.stopped_at = Some(self.pc);
+        self.ram.take_hit();
+
+        Ok(())
+    }
+}
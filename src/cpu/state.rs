@@ -0,0 +1,205 @@
+// Versioned binary snapshots of a Cpu. Every integer is little endian, the
+// header is the magic, the format version as a u16, the platform as a u8 and
+// the SHA-1 of the rom, followed by the machine state in the order
+// save_state writes it.
+
+use super::{Cpu, HIRES_SCREEN_HEIGHT, HIRES_SCREEN_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+use super::{AUDIO_PATTERN_SIZE, STACK_SIZE};
+use crate::display::Display;
+use crate::error::StateError;
+use crate::platform::Platform;
+use crate::rng::Rng;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"C8ST";
+const STATE_VERSION: u16 = 1;
+
+impl Cpu {
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&STATE_VERSION.to_le_bytes());
+        out.push(platform_id(self.platform));
+        out.extend_from_slice(&self.rom_hash);
+
+        out.extend_from_slice(&self.pc.to_le_bytes());
+        out.extend_from_slice(&self.i_reg.to_le_bytes());
+        out.push(self.stack_pointer);
+        for addr in self.stack {
+            out.extend_from_slice(&addr.to_le_bytes());
+        }
+        out.extend_from_slice(&self.v_reg);
+        out.push(self.delay_timer);
+        out.push(self.sound_timer);
+
+        let keys = (0..16)
+            .filter(|&key| self.keypad[key])
+            .fold(0u16, |keys, key| keys | 1 << key);
+        out.extend_from_slice(&keys.to_le_bytes());
+
+        out.extend_from_slice(&self.rpl_flags);
+        out.push(self.planes);
+        out.extend_from_slice(&self.audio_pattern);
+        out.push(self.pitch);
+        out.push(self.halted as u8);
+        out.extend_from_slice(&self.rng.state().to_le_bytes());
+        out.extend_from_slice(&self.released_keys.to_le_bytes());
+        out.push(self.waiting_for_key as u8);
+
+        out.extend_from_slice(&(self.display.width() as u16).to_le_bytes());
+        out.extend_from_slice(&(self.display.height() as u16).to_le_bytes());
+        out.extend_from_slice(&self.display);
+
+        let memory = self.ram.peek(0, self.ram.size()).unwrap();
+        out.extend_from_slice(&(memory.len() as u32).to_le_bytes());
+        out.extend_from_slice(memory);
+
+        out
+    }
+
+    // the state is checked completely before anything is changed
+    pub fn load_state(&mut self, state: &[u8]) -> Result<(), StateError> {
+        let mut reader = Reader { data: state };
+
+        if reader.bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(StateError::BadMagic);
+        }
+
+        let version = reader.u16()?;
+        if version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+        if reader.u8()? != platform_id(self.platform) {
+            return Err(StateError::PlatformMismatch);
+        }
+        if reader.bytes(20)? != self.rom_hash {
+            return Err(StateError::RomMismatch);
+        }
+
+        let pc = reader.u16()?;
+        let i_reg = reader.u16()?;
+        let stack_pointer = reader.u8()?;
+        let mut stack = [0; STACK_SIZE];
+        for addr in stack.iter_mut() {
+            *addr = reader.u16()?;
+        }
+        let v_reg = reader.array()?;
+        let delay_timer = reader.u8()?;
+        let sound_timer = reader.u8()?;
+        let keys = reader.u16()?;
+        let rpl_flags = reader.array()?;
+        let planes = reader.u8()?;
+        let audio_pattern: [u8; AUDIO_PATTERN_SIZE] = reader.array()?;
+        let pitch = reader.u8()?;
+        let halted = reader.u8()?;
+        let rng = Rng::new(reader.u64()?);
+        let released_keys = reader.u16()?;
+        let waiting_for_key = reader.u8()?;
+
+        let width = reader.u16()? as usize;
+        let height = reader.u16()? as usize;
+        let pixels = reader.bytes(width * height)?;
+        let memory_size = reader.u32()? as usize;
+        let memory = reader.bytes(memory_size)?;
+
+        let resolution_ok = (width, height) == (SCREEN_WIDTH, SCREEN_HEIGHT)
+            || (width, height) == (HIRES_SCREEN_WIDTH, HIRES_SCREEN_HEIGHT);
+
+        if !reader.data.is_empty()
+            || stack_pointer as usize > STACK_SIZE
+            || planes > 0b11
+            || halted > 1
+            || waiting_for_key > 1
+            || !resolution_ok
+            || pixels.iter().any(|pixel| *pixel > 0b11)
+            || memory_size != self.ram.size()
+        {
+            return Err(StateError::Corrupt);
+        }
+
+        let mut display = Display::new(width, height);
+        display.copy_from_slice(pixels);
+
+        self.pc = pc;
+        self.i_reg = i_reg;
+        self.stack_pointer = stack_pointer;
+        self.stack = stack;
+        self.v_reg = v_reg;
+        self.delay_timer = delay_timer;
+        self.sound_timer = sound_timer;
+        self.keypad = std::array::from_fn(|key| keys & 1 << key != 0);
+        self.rpl_flags = rpl_flags;
+        self.planes = planes;
+        self.audio_pattern = audio_pattern;
+        self.pitch = pitch;
+        self.halted = halted == 1;
+        self.rng = rng;
+        self.released_keys = released_keys;
+        self.waiting_for_key = waiting_for_key == 1;
+        self.display = display;
+        self.ram.restore(memory);
+        self.stopped_at = None;
+        self.draw_flag = true;
+
+        Ok(())
+    }
+
+    pub fn save_state_to_file(&self, path: &Path) -> Result<(), StateError> {
+        std::fs::write(path, self.save_state())?;
+
+        Ok(())
+    }
+
+    pub fn load_state_from_file(&mut self, path: &Path) -> Result<(), StateError> {
+        let state = std::fs::read(path)?;
+
+        self.load_state(&state)
+    }
+}
+
+// stored ids must never change, new platforms get new numbers
+fn platform_id(platform: Platform) -> u8 {
+    match platform {
+        Platform::Chip8 => 0,
+        Platform::SuperChip => 1,
+        Platform::XoChip => 2,
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], StateError> {
+        if self.data.len() < length {
+            return Err(StateError::Corrupt);
+        }
+
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], StateError> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+}
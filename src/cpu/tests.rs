@@ -1,38 +1,160 @@
 use super::*;
+use crate::error::{CpuError, StateError};
+use crate::mem::{Access, AccessKind, Watchpoint, MEM_SIZE};
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+use crate::rng::Rng;
 
 fn set_up_cpu(program: &[u8]) -> Cpu {
-    let mut cpu = Cpu::new();
-    cpu.ram.load_program(program);
+    set_up_cpu_with_quirks(program, Quirks::default())
+}
+
+fn set_up_cpu_with_quirks(program: &[u8], quirks: Quirks) -> Cpu {
+    set_up_cpu_for(program, Platform::Chip8, quirks)
+}
+
+fn set_up_cpu_for(program: &[u8], platform: Platform, quirks: Quirks) -> Cpu {
+    let mut cpu = Cpu::new(platform, quirks);
+    cpu.ram.load_program(program).unwrap();
 
     cpu
 }
 
+fn set_up_schip(program: &[u8]) -> Cpu {
+    set_up_cpu_for(program, Platform::SuperChip, Quirks::SUPER_CHIP)
+}
+
+fn set_up_xo(program: &[u8]) -> Cpu {
+    set_up_cpu_for(program, Platform::XoChip, Quirks::XO_CHIP)
+}
+
+#[test]
+fn test_00cn() {
+    let mut cpu = set_up_schip(&[0x00, 0xC2]);
+    cpu.display[3] = 1;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[3], 0);
+    assert_eq!(cpu.display[2 * SCREEN_WIDTH + 3], 1);
+}
+
+#[test]
+fn test_00dn() {
+    let mut cpu = set_up_xo(&[0x00, 0xD1]);
+    cpu.display[SCREEN_WIDTH + 3] = 1;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[3], 1);
+    assert_eq!(cpu.display[SCREEN_WIDTH + 3], 0);
+}
+
 #[test]
 fn test_00e0() {
     let mut cpu = set_up_cpu(&[0x00, 0xE0]);
-    cpu.display[13] = true;
-    cpu.display[45] = true;
+    cpu.display[13] = 1;
+    cpu.display[45] = 1;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
-    assert!(cpu.display.iter().all(|px| *px == false));
+    assert!(cpu.display.iter().all(|px| *px == 0));
 }
 
 #[test]
 fn test_00ee() {
     let mut cpu = set_up_cpu(&[0x00, 0xEE]);
-    cpu.stack_push(599);
+    cpu.stack_push(599).ok();
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 599);
 }
 
+#[test]
+fn test_00e0_selected_planes() {
+    let mut cpu = set_up_xo(&[0xF2, 0x01, 0x00, 0xE0]);
+    cpu.display[13] = 0b11;
+    cpu.display[45] = 0b01;
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[13], 0b01);
+    assert_eq!(cpu.display[45], 0b01);
+}
+
+#[test]
+fn test_00fb() {
+    let mut cpu = set_up_schip(&[0x00, 0xFB]);
+    cpu.display[SCREEN_WIDTH - 1] = 1;
+    cpu.display[SCREEN_WIDTH] = 1;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[SCREEN_WIDTH], 0);
+    assert_eq!(cpu.display[SCREEN_WIDTH + 4], 1);
+    assert!(cpu.display[0..SCREEN_WIDTH].iter().all(|px| *px == 0));
+}
+
+#[test]
+fn test_00fc() {
+    let mut cpu = set_up_schip(&[0x00, 0xFC]);
+    cpu.display[SCREEN_WIDTH + 5] = 1;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[SCREEN_WIDTH + 1], 1);
+    assert_eq!(cpu.display[SCREEN_WIDTH + 5], 0);
+}
+
+#[test]
+fn test_00fd() {
+    let mut cpu = set_up_schip(&[0x00, 0xFD, 0x60, 0x01]);
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert!(cpu.is_halted());
+    assert_eq!(cpu.pc, 0x202);
+    assert_eq!(cpu.v_reg[0x0], 0);
+}
+
+#[test]
+fn test_00fe_00ff() {
+    let mut cpu = set_up_schip(&[0x00, 0xFF, 0x00, 0xFE]);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display.width(), HIRES_SCREEN_WIDTH);
+    assert_eq!(cpu.display.height(), HIRES_SCREEN_HEIGHT);
+    assert_eq!(cpu.display.len(), HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display.width(), SCREEN_WIDTH);
+    assert_eq!(cpu.display.height(), SCREEN_HEIGHT);
+}
+
+#[test]
+fn test_schip_opcodes_unknown_on_chip8() {
+    let mut cpu = set_up_cpu(&[0x00, 0xFF]);
+
+    assert_eq!(
+        cpu.tick(),
+        Err(CpuError::UnknownOpcode {
+            pc: 0x200,
+            opcode: 0x00FF
+        })
+    );
+    assert_eq!(cpu.display.width(), SCREEN_WIDTH);
+}
+
 #[test]
 fn test_1nnn() {
     let mut cpu = set_up_cpu(&[0x1A, 0xBC]);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0xABC);
 }
@@ -41,24 +163,24 @@ fn test_1nnn() {
 fn test_2nnn() {
     let mut cpu = set_up_cpu(&[0x23, 0xCD]);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x3CD);
-    assert_eq!(cpu.stack_pop(), 0x202);
+    assert_eq!(cpu.stack_pop().ok(), Some(0x202));
 }
 
 #[test]
 fn test_3xnn() {
-    let mut cpu = set_up_cpu(&[0x30, 0xEE, 0x30, 0xAA]);
+    let mut cpu = set_up_cpu(&[0x30, 0xEE, 0x00, 0xE0, 0x30, 0xAA]);
     cpu.v_reg[0] = 0xEE;
 
     assert_eq!(cpu.pc, 0x200);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x204);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x206);
 }
@@ -67,7 +189,7 @@ fn test_3xnn() {
 fn test_4xnn() {
     let mut cpu = set_up_cpu(&[0x40, 0x88]);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x204);
 }
@@ -78,16 +200,44 @@ fn test_5xy0() {
     cpu.v_reg[0xA] = 0xFF;
     cpu.v_reg[0xF] = 0xFF;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x204);
 }
 
+#[test]
+fn test_5xy2() {
+    let mut cpu = set_up_xo(&[0x51, 0x32, 0x53, 0x12]);
+    cpu.v_reg[1..=3].copy_from_slice(&[0x11, 0x22, 0x33]);
+    cpu.i_reg = 400;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.ram.read(400, 3).unwrap(), &[0x11, 0x22, 0x33]);
+    assert_eq!(cpu.i_reg, 400);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.ram.read(400, 3).unwrap(), &[0x33, 0x22, 0x11]);
+}
+
+#[test]
+fn test_5xy3() {
+    let mut cpu = set_up_xo(&[0x52, 0x43]);
+    cpu.ram.write(400, &[0x12, 0x34, 0x56]).unwrap();
+    cpu.i_reg = 400;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(&cpu.v_reg[2..=4], &[0x12, 0x34, 0x56]);
+    assert_eq!(cpu.i_reg, 400);
+}
+
 #[test]
 fn test_6xnn() {
     let mut cpu = set_up_cpu(&[0x6B, 0x32]);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0xB], 0x32);
 }
@@ -97,7 +247,7 @@ fn test_7xnn() {
     let mut cpu = set_up_cpu(&[0x7A, 0xC]);
     cpu.v_reg[0xA] = 0x3;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0xA], 0xF);
 }
@@ -107,7 +257,7 @@ fn test_8xy0() {
     let mut cpu = set_up_cpu(&[0x80, 0x10]);
     cpu.v_reg[0x1] = 0x12;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0x12);
 }
@@ -118,7 +268,7 @@ fn test_8xy1() {
     cpu.v_reg[0x0] = 0b1001;
     cpu.v_reg[0x1] = 0b1101;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0b1101);
 }
@@ -129,7 +279,7 @@ fn test_8xy2() {
     cpu.v_reg[0x0] = 0b0011;
     cpu.v_reg[0x1] = 0b1010;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0b10);
 }
@@ -140,7 +290,7 @@ fn test_8xy3() {
     cpu.v_reg[0x0] = 0b1001;
     cpu.v_reg[0x1] = 0b1101;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0b100);
 }
@@ -151,14 +301,14 @@ fn test_8xy4() {
     cpu.v_reg[0x0] = 0x1B;
     cpu.v_reg[0x1] = 0xC;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0x27);
     assert_eq!(cpu.v_reg[0xF], 0);
 
     cpu.v_reg[0x1] = 0xF1;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0x18);
     assert_eq!(cpu.v_reg[0xF], 1);
@@ -170,7 +320,7 @@ fn test_8xy5() {
     cpu.v_reg[0x0] = 0xA;
     cpu.v_reg[0x1] = 0x6;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0x0], 0x4);
     assert_eq!(cpu.v_reg[0xF], 1);
@@ -181,19 +331,31 @@ fn test_8xy6() {
     let mut cpu = set_up_cpu(&[0x80, 0x16]);
     cpu.v_reg[0x0] = 0b10010;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0xF], 0);
     assert_eq!(cpu.v_reg[0x0], 0b1001);
 }
 
+#[test]
+fn test_8xy6_shift_uses_vy() {
+    let mut cpu = set_up_cpu_with_quirks(&[0x80, 0x16], Quirks::COSMAC_VIP);
+    cpu.v_reg[0x0] = 0b10010;
+    cpu.v_reg[0x1] = 0b111;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.v_reg[0xF], 1);
+    assert_eq!(cpu.v_reg[0x0], 0b11);
+}
+
 #[test]
 fn test_8xy7() {
     let mut cpu = set_up_cpu(&[0x80, 0x17]);
     cpu.v_reg[0x0] = 0x43;
     cpu.v_reg[0x1] = 0x30;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0xF], 0);
     assert_eq!(cpu.v_reg[0x0], 0xED);
@@ -204,19 +366,44 @@ fn test_8xye() {
     let mut cpu = set_up_cpu(&[0x80, 0x0E]);
     cpu.v_reg[0x0] = 0b1001_1000;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0xF], 1);
     assert_eq!(cpu.v_reg[0x0], 0b11_0000);
 }
 
+#[test]
+fn test_8xye_shift_uses_vy() {
+    let mut cpu = set_up_cpu_with_quirks(&[0x80, 0x1E], Quirks::COSMAC_VIP);
+    cpu.v_reg[0x0] = 0b1001_1000;
+    cpu.v_reg[0x1] = 0b0100_0001;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.v_reg[0xF], 0);
+    assert_eq!(cpu.v_reg[0x0], 0b1000_0010);
+}
+
+#[test]
+fn test_8xy1_logic_resets_vf() {
+    let mut cpu = set_up_cpu_with_quirks(&[0x80, 0x11], Quirks::COSMAC_VIP);
+    cpu.v_reg[0x0] = 0b1001;
+    cpu.v_reg[0x1] = 0b1101;
+    cpu.v_reg[0xF] = 1;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.v_reg[0x0], 0b1101);
+    assert_eq!(cpu.v_reg[0xF], 0);
+}
+
 #[test]
 fn test_9xy0() {
     let mut cpu = set_up_cpu(&[0x90, 0x10]);
     cpu.v_reg[0x0] = 0x12;
     cpu.v_reg[0x1] = 0x13;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x204);
 }
@@ -225,7 +412,7 @@ fn test_9xy0() {
 fn test_annn() {
     let mut cpu = set_up_cpu(&[0xA1, 0xFC]);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.i_reg, 0x1FC);
 }
@@ -235,38 +422,155 @@ fn test_bnnn() {
     let mut cpu = set_up_cpu(&[0xBA, 0x11]);
     cpu.v_reg[0x0] = 0x3;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0xA14);
 }
 
+#[test]
+fn test_bnnn_jump_uses_vx() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xBA, 0x11], Quirks::CHIP_48);
+    cpu.v_reg[0x0] = 0x3;
+    cpu.v_reg[0xA] = 0x5;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0xA16);
+}
+
 #[test]
 fn test_dxyn() {
     let mut cpu = set_up_cpu(&[0xD0, 0x12]);
-    cpu.display[SCREEN_WIDTH] = true;
-    cpu.ram.write(0, &[0b1011_0001, 0b1111_0010]);
+    cpu.display[SCREEN_WIDTH] = 1;
+    cpu.ram.write(0, &[0b1011_0001, 0b1111_0010]).unwrap();
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
-    assert_eq!(
-        &cpu.display[0..8],
-        &[true, false, true, true, false, false, false, true]
-    );
+    assert_eq!(&cpu.display[0..8], &[1, 0, 1, 1, 0, 0, 0, 1]);
     assert_eq!(
         &cpu.display[SCREEN_WIDTH..SCREEN_WIDTH + 8],
-        &[false, true, true, true, false, false, true, false]
+        &[0, 1, 1, 1, 0, 0, 1, 0]
     );
 
     assert_eq!(cpu.v_reg[0xF], 1);
 }
 
+#[test]
+fn test_dxyn_wrap() {
+    let quirks = Quirks {
+        clip_sprites: false,
+        ..Quirks::default()
+    };
+    let mut cpu = set_up_cpu_with_quirks(&[0xD0, 0x12], quirks);
+    cpu.v_reg[0x0] = SCREEN_WIDTH as u8 - 4;
+    cpu.v_reg[0x1] = SCREEN_HEIGHT as u8 - 1;
+    cpu.ram.write(0, &[0b1111_1111, 0b1000_0001]).unwrap();
+
+    cpu.tick().unwrap();
+
+    let last_row = (SCREEN_HEIGHT - 1) * SCREEN_WIDTH;
+    assert!(cpu.display[last_row + SCREEN_WIDTH - 4..]
+        .iter()
+        .all(|px| *px == 1));
+    assert!(cpu.display[last_row..last_row + 4]
+        .iter()
+        .all(|px| *px == 1));
+    assert_eq!(&cpu.display[SCREEN_WIDTH - 4..SCREEN_WIDTH], &[1, 0, 0, 0]);
+    assert_eq!(&cpu.display[0..4], &[0, 0, 0, 1]);
+}
+
+#[test]
+fn test_dxyn_clip() {
+    let mut cpu = set_up_cpu(&[0xD0, 0x12]);
+    cpu.v_reg[0x0] = SCREEN_WIDTH as u8 - 4;
+    cpu.v_reg[0x1] = SCREEN_HEIGHT as u8 - 1;
+    cpu.ram.write(0, &[0b1111_1111, 0b1000_0001]).unwrap();
+
+    cpu.tick().unwrap();
+
+    let last_row = (SCREEN_HEIGHT - 1) * SCREEN_WIDTH;
+    assert!(cpu.display[last_row + SCREEN_WIDTH - 4..]
+        .iter()
+        .all(|px| *px == 1));
+    assert!(cpu.display[0..last_row + SCREEN_WIDTH - 4]
+        .iter()
+        .all(|px| *px == 0));
+}
+
+#[test]
+fn test_dxy0() {
+    let mut cpu = set_up_schip(&[0x00, 0xFF, 0xD0, 0x10]);
+    let mut sprite = [0u8; 32];
+    sprite[0] = 0b1000_0000;
+    sprite[1] = 0b0000_0001;
+    sprite[31] = 0b0000_0001;
+    cpu.ram.write(0x300, &sprite).unwrap();
+    cpu.i_reg = 0x300;
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[0], 1);
+    assert_eq!(cpu.display[15], 1);
+    assert_eq!(cpu.display[15 * HIRES_SCREEN_WIDTH + 15], 1);
+    assert_eq!(cpu.display.iter().filter(|px| **px != 0).count(), 3);
+    assert_eq!(cpu.v_reg[0xF], 0);
+}
+
+#[test]
+fn test_dxy0_low_resolution() {
+    let mut cpu = set_up_schip(&[0xD0, 0x10]);
+    let mut sprite = [0u8; 32];
+    sprite[0] = 0b1000_0001;
+    sprite[1] = 0b1000_0000;
+    sprite[15] = 0b0000_0001;
+    cpu.ram.write(0x300, &sprite).unwrap();
+    cpu.i_reg = 0x300;
+
+    cpu.tick().unwrap();
+
+    // one byte per row, 16 rows
+    assert_eq!(cpu.display[0], 1);
+    assert_eq!(cpu.display[7], 1);
+    assert_eq!(cpu.display[SCREEN_WIDTH], 1);
+    assert_eq!(cpu.display[15 * SCREEN_WIDTH + 7], 1);
+    assert_eq!(cpu.display.iter().filter(|px| **px != 0).count(), 4);
+}
+
+#[test]
+fn test_dxy0_xo_low_resolution() {
+    let mut cpu = set_up_xo(&[0xD0, 0x10]);
+    let mut sprite = [0u8; 32];
+    sprite[1] = 0b0000_0001;
+    sprite[31] = 0b0000_0001;
+    cpu.ram.write(0x300, &sprite).unwrap();
+    cpu.i_reg = 0x300;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.display[15], 1);
+    assert_eq!(cpu.display[15 * SCREEN_WIDTH + 15], 1);
+}
+
+#[test]
+fn test_dxyn_planes() {
+    let mut cpu = set_up_xo(&[0xF3, 0x01, 0xD0, 0x11]);
+    cpu.ram.write(0x300, &[0b1100_0000, 0b1010_0000]).unwrap();
+    cpu.i_reg = 0x300;
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(&cpu.display[0..4], &[0b11, 0b01, 0b10, 0]);
+}
+
 #[test]
 fn test_ex9e() {
     let mut cpu = set_up_cpu(&[0xE0, 0x9E]);
     cpu.v_reg[0x0] = 0x7;
     cpu.keypad[0x7] = true;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x204)
 }
@@ -277,17 +581,48 @@ fn test_exa1() {
     cpu.v_reg[0x0] = 0x7;
     cpu.keypad[0x7] = false;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x204)
 }
 
+#[test]
+fn test_f000() {
+    let mut cpu = set_up_xo(&[0xF0, 0x00, 0xAB, 0xCD]);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.i_reg, 0xABCD);
+    assert_eq!(cpu.pc, 0x204);
+}
+
+#[test]
+fn test_skip_f000() {
+    let mut cpu = set_up_xo(&[0x30, 0x00, 0xF0, 0x00, 0xAB, 0xCD]);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x206);
+}
+
+#[test]
+fn test_f002() {
+    let mut cpu = set_up_xo(&[0xF0, 0x02]);
+    let pattern: Vec<u8> = (0..16).collect();
+    cpu.ram.write(0x300, &pattern).unwrap();
+    cpu.i_reg = 0x300;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(&cpu.audio_pattern[..], &pattern[..]);
+}
+
 #[test]
 fn test_fx07() {
     let mut cpu = set_up_cpu(&[0xFC, 0x07]);
     cpu.delay_timer = 41;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.v_reg[0xC], 41);
 }
@@ -296,24 +631,119 @@ fn test_fx07() {
 fn test_fx0a() {
     let mut cpu = set_up_cpu(&[0xF0, 0x0A]);
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x200);
 
     cpu.keypad[0xE] = true;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.pc, 0x202);
     assert_eq!(cpu.v_reg[0x0], 0xE);
 }
 
+#[test]
+fn test_fx0a_waits_for_release() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xF0, 0x0A], Quirks::COSMAC_VIP);
+
+    cpu.key_press(0x7);
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x200);
+    assert!(cpu.is_waiting_for_key());
+
+    cpu.key_release(0x7);
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x202);
+    assert_eq!(cpu.v_reg[0x0], 0x7);
+    assert!(!cpu.is_waiting_for_key());
+}
+
+#[test]
+fn test_fx0a_ignores_earlier_releases() {
+    let mut cpu = set_up_cpu_with_quirks(&[0x60, 0x00, 0xF1, 0x0A], Quirks::COSMAC_VIP);
+
+    // released before FX0A ran
+    cpu.key_press(0x2);
+    cpu.key_release(0x2);
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x202);
+
+    cpu.key_press(0x4);
+    cpu.key_release(0x4);
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x204);
+    assert_eq!(cpu.v_reg[0x1], 0x4);
+}
+
+#[test]
+fn test_fx0a_held_key_doesnt_satisfy_next_wait() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xF0, 0x0A, 0xF1, 0x0A], Quirks::COSMAC_VIP);
+
+    cpu.key_press(0x5);
+    cpu.tick().unwrap();
+    cpu.key_release(0x5);
+    cpu.tick().unwrap();
+    cpu.key_press(0x5);
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn test_fx0a_at_end_of_memory() {
+    let mut cpu = set_up_xo(&[]);
+    cpu.ram.write(0xFFFE, &[0xF0, 0x0A]).unwrap();
+    cpu.pc = 0xFFFE;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0xFFFE);
+    assert!(cpu.is_waiting_for_key());
+}
+
+#[test]
+fn test_keys_out_of_range_are_ignored() {
+    let mut cpu = set_up_cpu(&[0xF0, 0x0A]);
+
+    cpu.key_press(0x10);
+    cpu.key_release(0x10);
+    cpu.key_press(usize::MAX);
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.keypad, [false; 16]);
+    assert_eq!(cpu.released_keys, 0);
+    assert!(cpu.is_waiting_for_key());
+}
+
+#[test]
+fn test_timers_run_while_waiting_for_key() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xF0, 0x0A], Quirks::COSMAC_VIP);
+    cpu.delay_timer = 3;
+    cpu.sound_timer = 2;
+
+    for _ in 0..2 {
+        cpu.tick().unwrap();
+        cpu.tick_timers();
+    }
+
+    assert_eq!(cpu.pc, 0x200);
+    assert_eq!(cpu.delay_timer, 1);
+    assert!(!cpu.is_beeping());
+}
+
 #[test]
 fn test_fx15() {
     let mut cpu = set_up_cpu(&[0xF0, 0x15]);
     cpu.v_reg[0x0] = 56;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.delay_timer, 56);
 }
@@ -323,41 +753,89 @@ fn test_fx18() {
     let mut cpu = set_up_cpu(&[0xF0, 0x18]);
     cpu.v_reg[0x0] = 56;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.sound_timer, 56);
 }
 
+#[test]
+fn test_is_beeping() {
+    let mut cpu = set_up_cpu(&[0xF0, 0x18]);
+    cpu.v_reg[0x0] = 1;
+
+    assert!(!cpu.is_beeping());
+
+    cpu.tick().unwrap();
+
+    assert!(cpu.is_beeping());
+
+    cpu.tick_timers();
+
+    assert!(!cpu.is_beeping());
+}
+
 #[test]
 fn test_fx1e() {
     let mut cpu = set_up_cpu(&[0xF0, 0x1E]);
     cpu.i_reg = 32;
     cpu.v_reg[0x0] = 56;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.i_reg, 88);
 }
 
 #[test]
+#[allow(clippy::unnecessary_cast)]
 fn test_fx29() {
     let mut cpu = set_up_cpu(&[0xF0, 0x29]);
     cpu.v_reg[0x0] = 1;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(cpu.i_reg, FONT_ADDRESS as u16 + 5);
 }
 
+#[test]
+fn test_fx30() {
+    let mut cpu = set_up_schip(&[0xF0, 0x30]);
+    cpu.v_reg[0x0] = 2;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.i_reg, BIG_FONT_ADDRESS + 20);
+}
+
 #[test]
 fn test_fx33() {
     let mut cpu = set_up_cpu(&[0xF0, 0x33]);
     cpu.v_reg[0x0] = 149;
     cpu.i_reg = 400;
 
-    cpu.tick();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.ram.read(400, 3).unwrap(), &[0x1, 0x4, 0x9]);
+}
+
+#[test]
+fn test_fx3a() {
+    let mut cpu = set_up_xo(&[0xF5, 0x3A]);
+    cpu.v_reg[0x5] = 112;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pitch, 112);
+}
+
+#[test]
+fn test_xo_memory() {
+    let mut cpu = set_up_xo(&[0xF0, 0x00, 0xFF, 0x00, 0xF0, 0x55]);
+    cpu.v_reg[0x0] = 0x42;
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
 
-    assert_eq!(cpu.ram.read(400, 3), &[0x1, 0x4, 0x9]);
+    assert_eq!(cpu.ram.read(0xFF00, 1).unwrap(), &[0x42]);
 }
 
 #[test]
@@ -366,18 +844,554 @@ fn test_fx55() {
     cpu.v_reg[0..=4].copy_from_slice(&[0x0, 0x1, 0x2, 0x3, 0x21]);
     cpu.i_reg = 400;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
-    assert_eq!(cpu.ram.read(400, 5), &[0x0, 0x1, 0x2, 0x3, 0x21]);
+    assert_eq!(cpu.ram.read(400, 5).unwrap(), &[0x0, 0x1, 0x2, 0x3, 0x21]);
 }
 
 #[test]
 fn test_fx65() {
     let mut cpu = set_up_cpu(&[0xF3, 0x65]);
-    cpu.ram.write(400, &[0x12, 0x34, 0x56, 0x78]);
+    cpu.ram.write(400, &[0x12, 0x34, 0x56, 0x78]).unwrap();
     cpu.i_reg = 400;
 
-    cpu.tick();
+    cpu.tick().unwrap();
 
     assert_eq!(&cpu.v_reg[0..=3], &[0x12, 0x34, 0x56, 0x78]);
 }
+
+#[test]
+fn test_fx55_memory_increment() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xF4, 0x55], Quirks::COSMAC_VIP);
+    cpu.i_reg = 400;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.i_reg, 405);
+}
+
+#[test]
+fn test_fx65_memory_increment() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xF3, 0x65], Quirks::CHIP_48);
+    cpu.i_reg = 400;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.i_reg, 403);
+}
+
+#[test]
+fn test_fx75_fx85() {
+    let mut cpu = set_up_schip(&[0xF2, 0x75, 0x60, 0x00, 0xF2, 0x85]);
+    cpu.v_reg[0..=2].copy_from_slice(&[0x12, 0x34, 0x56]);
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.v_reg[0x0], 0);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(&cpu.v_reg[0..=2], &[0x12, 0x34, 0x56]);
+}
+
+#[test]
+fn test_stack_overflow() {
+    let mut cpu = set_up_cpu(&[0x22, 0x00]);
+
+    for _ in 0..STACK_SIZE {
+        cpu.tick().unwrap();
+    }
+
+    assert_eq!(
+        cpu.tick(),
+        Err(CpuError::StackOverflow {
+            pc: 0x200,
+            opcode: 0x2200
+        })
+    );
+}
+
+#[test]
+fn test_stack_underflow() {
+    let mut cpu = set_up_cpu(&[0x00, 0xEE]);
+
+    assert_eq!(
+        cpu.tick(),
+        Err(CpuError::StackUnderflow {
+            pc: 0x200,
+            opcode: 0x00EE
+        })
+    );
+}
+
+#[test]
+fn test_memory_out_of_bounds() {
+    let mut cpu = set_up_cpu(&[0xAF, 0xFE, 0xF3, 0x55]);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(
+        cpu.tick(),
+        Err(CpuError::MemoryOutOfBounds {
+            pc: 0x202,
+            opcode: 0xF355,
+            addr: MEM_SIZE
+        })
+    );
+}
+
+#[test]
+fn test_fetch_out_of_bounds() {
+    let mut cpu = set_up_cpu(&[0x1F, 0xFF]);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(
+        cpu.tick(),
+        Err(CpuError::MemoryOutOfBounds {
+            pc: 0xFFF,
+            opcode: 0,
+            addr: MEM_SIZE
+        })
+    );
+}
+
+#[test]
+fn test_fx1e_wraps() {
+    let mut cpu = set_up_xo(&[0xF0, 0x1E]);
+    cpu.i_reg = 0xFFFF;
+    cpu.v_reg[0x0] = 2;
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.i_reg, 1);
+}
+
+#[test]
+fn test_rom_too_big() {
+    let mut cpu = Cpu::new(Platform::Chip8, Quirks::default());
+
+    assert!(cpu.load_program(&[0; MEM_SIZE]).is_err());
+}
+
+#[test]
+fn test_unknown_opcode_ignore() {
+    let mut cpu = set_up_cpu(&[0x01, 0x23]);
+    cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Ignore);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.pc, 0x202);
+    assert!(cpu.take_unknown_opcodes().is_empty());
+}
+
+#[test]
+fn test_unknown_opcode_log() {
+    let mut cpu = set_up_cpu(&[0x01, 0x23, 0x5A, 0xB4]);
+    cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Log);
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    let logged = cpu.take_unknown_opcodes();
+    assert_eq!(logged.len(), 2);
+    assert_eq!(
+        logged[0].diagnostic(Platform::Chip8),
+        "unknown opcode (pc: 0x0200, opcode: 0123)\n0x0200: 0123  SYS 0x123"
+    );
+    assert_eq!(
+        logged[1].diagnostic(Platform::Chip8),
+        "unknown opcode (pc: 0x0202, opcode: 5AB4)\n0x0202: 5AB4  ???"
+    );
+    assert!(cpu.take_unknown_opcodes().is_empty());
+}
+
+#[test]
+fn test_state_roundtrip() {
+    let mut cpu = Cpu::new(Platform::SuperChip, Quirks::SUPER_CHIP);
+    cpu.load_program(&[0x00, 0xFF, 0x6A, 0x02, 0x22, 0x08])
+        .unwrap();
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+    cpu.display[5] = 1;
+    cpu.delay_timer = 7;
+    cpu.key_press(0xB);
+    let state = cpu.save_state();
+
+    let mut restored = Cpu::new(Platform::SuperChip, Quirks::SUPER_CHIP);
+    restored
+        .load_program(&[0x00, 0xFF, 0x6A, 0x02, 0x22, 0x08])
+        .unwrap();
+    restored.load_state(&state).unwrap();
+
+    assert_eq!(restored.pc, 0x208);
+    assert_eq!(restored.stack_pointer, 1);
+    assert_eq!(restored.stack[0], 0x206);
+    assert_eq!(restored.v_reg[0xA], 0x02);
+    assert_eq!(restored.delay_timer, 7);
+    assert!(restored.keypad[0xB]);
+    assert_eq!(restored.display.width(), HIRES_SCREEN_WIDTH);
+    assert_eq!(restored.display[5], 1);
+    assert_eq!(restored.save_state(), state);
+}
+
+#[test]
+fn test_state_rom_mismatch() {
+    let mut cpu = Cpu::new(Platform::Chip8, Quirks::default());
+    cpu.load_program(&[0x60, 0x01]).unwrap();
+    let state = cpu.save_state();
+
+    let mut other = Cpu::new(Platform::Chip8, Quirks::default());
+    other.load_program(&[0x60, 0x02]).unwrap();
+
+    assert!(matches!(
+        other.load_state(&state),
+        Err(StateError::RomMismatch)
+    ));
+}
+
+#[test]
+fn test_state_platform_mismatch() {
+    let state = Cpu::new(Platform::Chip8, Quirks::default()).save_state();
+    let mut cpu = Cpu::new(Platform::XoChip, Quirks::XO_CHIP);
+
+    assert!(matches!(
+        cpu.load_state(&state),
+        Err(StateError::PlatformMismatch)
+    ));
+}
+
+#[test]
+fn test_state_rejects_bad_data() {
+    let mut cpu = set_up_cpu(&[0x60, 0x01]);
+    let state = cpu.save_state();
+    let mut newer = state.clone();
+    newer[4] = 0xFF;
+
+    assert!(matches!(cpu.load_state(b"nope"), Err(StateError::BadMagic)));
+    assert!(matches!(
+        cpu.load_state(&newer),
+        Err(StateError::UnsupportedVersion(0xFF))
+    ));
+    assert!(matches!(
+        cpu.load_state(&state[..state.len() - 1]),
+        Err(StateError::Corrupt)
+    ));
+}
+
+#[test]
+fn test_cxnn() {
+    let program = [0xC0, 0xFF, 0xC1, 0x0F];
+    let mut cpu = Cpu::with_seed(Platform::Chip8, Quirks::default(), 42);
+    let mut same = Cpu::with_seed(Platform::Chip8, Quirks::default(), 42);
+    cpu.load_program(&program).unwrap();
+    same.load_program(&program).unwrap();
+
+    for _ in 0..2 {
+        cpu.tick().unwrap();
+        same.tick().unwrap();
+    }
+
+    let mut rng = Rng::new(42);
+    assert_eq!(cpu.v_reg[0], rng.next_u8());
+    assert_eq!(cpu.v_reg[1], rng.next_u8() & 0x0F);
+    assert_eq!(cpu.v_reg, same.v_reg);
+}
+
+#[test]
+fn test_state_keeps_rng() {
+    let mut cpu = Cpu::with_seed(Platform::Chip8, Quirks::default(), 7);
+    cpu.load_program(&[0xC0, 0xFF, 0x12, 0x00]).unwrap();
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+    let state = cpu.save_state();
+    cpu.tick().unwrap();
+    let expected = cpu.v_reg[0];
+
+    cpu.load_state(&state).unwrap();
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.v_reg[0], expected);
+}
+
+#[test]
+fn test_state_keeps_key_wait() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xF0, 0x0A], Quirks::COSMAC_VIP);
+    cpu.key_press(0x3);
+    cpu.tick().unwrap();
+    let state = cpu.save_state();
+
+    let mut other = set_up_cpu_with_quirks(&[0xF0, 0x0A], Quirks::COSMAC_VIP);
+    other.load_state(&state).unwrap();
+    other.key_release(0x3);
+    other.tick().unwrap();
+
+    assert_eq!(other.pc, 0x202);
+    assert_eq!(other.v_reg[0], 0x3);
+}
+
+#[test]
+fn test_run_frame() {
+    let mut cpu = set_up_cpu(&[0xD0, 0x01, 0x70, 0x01, 0x12, 0x00]);
+    cpu.delay_timer = 5;
+
+    assert_eq!(cpu.run_frame(10).unwrap(), None);
+    assert_eq!(cpu.instructions(), 10);
+    assert_eq!(cpu.delay_timer, 4);
+}
+
+#[test]
+fn test_display_wait_ends_frame() {
+    let mut cpu = set_up_cpu_with_quirks(&[0xD0, 0x01, 0x70, 0x01, 0x12, 0x00], Quirks::COSMAC_VIP);
+    cpu.delay_timer = 5;
+
+    cpu.run_frame(10).unwrap();
+
+    assert_eq!(cpu.instructions(), 1);
+    assert_eq!(cpu.delay_timer, 4);
+
+    // the add and the jump, then the next sprite
+    cpu.run_frame(10).unwrap();
+
+    assert_eq!(cpu.instructions(), 4);
+    assert_eq!(cpu.v_reg[0], 1);
+    assert_eq!(cpu.delay_timer, 3);
+}
+
+#[test]
+fn test_run_frame_breakpoint_skips_timers() {
+    let mut cpu = set_up_cpu(&[0x60, 0x01, 0x61, 0x02, 0x12, 0x00]);
+    cpu.delay_timer = 5;
+    cpu.add_breakpoint(0x202);
+
+    assert_eq!(cpu.run_frame(10).unwrap(), Some(Break::Breakpoint(0x202)));
+    assert_eq!(cpu.delay_timer, 5);
+}
+
+#[test]
+fn test_cycle_costs() {
+    let mut cpu = set_up_cpu(&[]);
+    cpu.v_reg[0] = 8;
+    cpu.v_reg[1] = 3;
+    cpu.v_reg[2] = 30;
+    cpu.v_reg[3] = 123;
+
+    assert_eq!(cpu.cycle_cost(0x6001), 46);
+    assert_eq!(cpu.cycle_cost(0x8124), 84);
+    // aligned sprites are cheaper than shifted ones, and clipped rows free
+    assert_eq!(cpu.cycle_cost(0xD015), 40 + 26 + 5 * 16);
+    assert_eq!(cpu.cycle_cost(0xD105), 40 + 26 + 5 * (16 + 12 + 8));
+    assert_eq!(cpu.cycle_cost(0xD025), 40 + 26 + 2 * 16);
+    assert_eq!(cpu.cycle_cost(0xF333), 40 + 80 + 8 * 6);
+    assert_eq!(cpu.cycle_cost(0xF255), 40 + 14 + 14 * 3);
+}
+
+#[test]
+fn test_vip_timing_runs_a_frame_of_cycles() {
+    // 46 and 52 cycles
+    let mut cpu = set_up_cpu(&[0x60, 0x01, 0x12, 0x00]);
+    cpu.set_timing(Timing::CosmacVip);
+    cpu.delay_timer = 5;
+
+    assert_eq!(cpu.run_frame(10).unwrap(), None);
+
+    // 26 loops leave 96 cycles, the last jump goes 2 over
+    assert_eq!(cpu.instructions(), 54);
+    assert_eq!(cpu.cycles, -2);
+    assert_eq!(cpu.delay_timer, 4);
+
+    // which this frame has fewer of
+    cpu.run_frame(10).unwrap();
+
+    assert_eq!(cpu.instructions(), 108);
+    assert_eq!(cpu.cycles, -4);
+}
+
+#[test]
+fn test_vip_timing_charges_skips() {
+    let mut cpu = set_up_cpu(&[0x30, 0x00, 0x00, 0xE0]);
+    cpu.set_timing(Timing::CosmacVip);
+
+    cpu.tick().unwrap();
+
+    assert_eq!(cpu.cycles, -(50 + 4));
+}
+
+#[test]
+fn test_registers() {
+    let mut cpu = set_up_cpu(&[0x22, 0x04, 0x00, 0x00, 0x6A, 0x07, 0xA1, 0x23]);
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    let registers = cpu.registers();
+
+    assert_eq!(registers.pc, 0x208);
+    assert_eq!(registers.i, 0x123);
+    assert_eq!(registers.sp, 1);
+    assert_eq!(registers.stack, vec![0x202]);
+    assert_eq!(registers.v[0xA], 0x07);
+}
+
+#[test]
+fn test_run_stops_at_breakpoint() {
+    let mut cpu = set_up_cpu(&[0x60, 0x01, 0x61, 0x02, 0x12, 0x00]);
+    cpu.add_breakpoint(0x202);
+
+    assert_eq!(cpu.run(10).unwrap(), Some(Break::Breakpoint(0x202)));
+    assert_eq!(cpu.pc, 0x202);
+    assert_eq!(cpu.v_reg[1], 0);
+
+    // running again passes over it until the loop comes back
+    assert_eq!(cpu.run(10).unwrap(), Some(Break::Breakpoint(0x202)));
+    assert_eq!(cpu.v_reg[1], 0x02);
+    assert_eq!(cpu.pc, 0x202);
+}
+
+#[test]
+fn test_run_without_breakpoints() {
+    let mut cpu = set_up_cpu(&[0x70, 0x01, 0x12, 0x00]);
+    cpu.add_breakpoint(0x300);
+
+    assert_eq!(cpu.run(10).unwrap(), None);
+    assert_eq!(cpu.v_reg[0], 5);
+    assert!(cpu.remove_breakpoint(0x300));
+    assert!(cpu.breakpoints().is_empty());
+}
+
+#[test]
+fn test_step_over_breakpoint() {
+    let mut cpu = set_up_cpu(&[0x60, 0x01, 0x61, 0x02, 0x62, 0x03]);
+    cpu.add_breakpoint(0x200);
+    cpu.add_breakpoint(0x202);
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.pc, 0x202);
+
+    // stepping onto a breakpoint doesn't stop the next run there
+    assert_eq!(cpu.run(2).unwrap(), None);
+    assert_eq!(cpu.pc, 0x206);
+}
+
+#[test]
+fn test_write_watchpoint() {
+    let mut cpu = set_up_cpu(&[0x60, 0x07, 0xA3, 0x00, 0xF0, 0x33, 0x12, 0x06]);
+    cpu.memory_mut().add_watchpoint(Watchpoint {
+        start: 0x301,
+        end: 0x301,
+        read: false,
+        write: true,
+        execute: false,
+    });
+
+    let hit = cpu.run(10).unwrap();
+
+    assert_eq!(
+        hit,
+        Some(Break::Watchpoint(Access {
+            pc: 0x204,
+            opcode: 0xF033,
+            addr: 0x301,
+            value: 0,
+            kind: AccessKind::Write,
+        }))
+    );
+    assert_eq!(cpu.pc, 0x206);
+}
+
+#[test]
+fn test_breakpoint_after_watchpoint() {
+    let mut cpu = set_up_cpu(&[0x60, 0x07, 0xA3, 0x00, 0xF0, 0x33, 0x12, 0x06]);
+    cpu.memory_mut().add_watchpoint(Watchpoint {
+        start: 0x301,
+        end: 0x301,
+        read: false,
+        write: true,
+        execute: false,
+    });
+    cpu.add_breakpoint(0x206);
+
+    assert!(matches!(cpu.run(10), Ok(Some(Break::Watchpoint(_)))));
+    assert_eq!(cpu.run(10), Ok(Some(Break::Breakpoint(0x206))));
+    assert_eq!(cpu.pc, 0x206);
+}
+
+#[test]
+fn test_execute_watchpoint() {
+    let mut cpu = set_up_cpu(&[0x60, 0x01, 0x61, 0x02, 0x12, 0x00]);
+    cpu.memory_mut().add_watchpoint(Watchpoint {
+        start: 0x202,
+        end: 0x203,
+        read: true,
+        write: true,
+        execute: true,
+    });
+
+    let hit = cpu.run(10).unwrap();
+
+    assert!(matches!(
+        hit,
+        Some(Break::Watchpoint(Access {
+            addr: 0x202,
+            kind: AccessKind::Execute,
+            ..
+        }))
+    ));
+    assert_eq!(cpu.v_reg[1], 0x02);
+    assert!(cpu.memory_mut().remove_watchpoint(0x202));
+    assert!(cpu.memory().watchpoints().is_empty());
+}
+
+#[test]
+fn test_trace() {
+    let mut cpu = set_up_cpu(&[0xA3, 0x00, 0xF1, 0x65]);
+    cpu.ram.write(0x300, &[0xAB, 0xCD]).unwrap();
+    cpu.memory_mut().start_trace(3);
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    let trace: Vec<(u16, u8, AccessKind)> = cpu
+        .memory_mut()
+        .take_trace()
+        .iter()
+        .map(|access| (access.addr, access.value, access.kind))
+        .collect();
+
+    // the oldest accesses fell out of the limit
+    assert_eq!(
+        trace,
+        vec![
+            (0x203, 0x65, AccessKind::Execute),
+            (0x300, 0xAB, AccessKind::Read),
+            (0x301, 0xCD, AccessKind::Read),
+        ]
+    );
+    assert!(cpu.memory_mut().take_trace().is_empty());
+}
+
+#[test]
+fn test_unlimited_trace() {
+    let mut cpu = set_up_cpu(&[0xA3, 0x00, 0xF1, 0x65]);
+    cpu.memory_mut().start_trace(0);
+
+    cpu.tick().unwrap();
+    cpu.tick().unwrap();
+
+    // both instructions and both bytes read
+    assert_eq!(cpu.memory_mut().take_trace().len(), 6);
+}
+
+#[test]
+fn test_watchpoints_survive_load_state() {
+    let mut cpu = set_up_cpu(&[0x60, 0x01]);
+    let state = cpu.save_state();
+    cpu.memory_mut().start_trace(16);
+
+    cpu.load_state(&state).unwrap();
+
+    assert!(cpu.memory().is_tracing());
+    assert!(cpu.memory_mut().take_trace().is_empty());
+}
@@ -0,0 +1,98 @@
+// Machine cycles of the COSMAC VIP interpreter. The 1802 runs at 1.76 MHz
+// with 8 clocks per machine cycle, 3668 cycles per 60 Hz frame, of which the
+// display DMA takes 1024 (128 lines of 8 bytes). The costs are rounded counts
+// of the 1802 instructions each CHIP-8 instruction runs, the fetch and decode
+// every instruction goes through included.
+
+use super::Cpu;
+
+// left for the interpreter every frame
+pub const VIP_FRAME_CYCLES: i64 = 3668 - 1024;
+const FETCH_CYCLES: i64 = 40;
+// extra for a skip that was taken
+pub(super) const SKIP_CYCLES: i64 = 4;
+
+/// How much of a program runs in one frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Timing {
+    /// the same number of instructions every frame, whatever they are
+    #[default]
+    Instructions,
+    /// as many as the COSMAC VIP gets through in a frame
+    CosmacVip,
+}
+
+impl Timing {
+    pub fn from_name(name: &str) -> Option<Timing> {
+        match name {
+            "ipf" => Some(Timing::Instructions),
+            "vip" => Some(Timing::CosmacVip),
+            _ => None,
+        }
+    }
+}
+
+impl Cpu {
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+    }
+
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    // machine cycles `opcode` takes, looked at before it runs since DXYN and
+    // FX33 depend on the registers
+    pub(super) fn cycle_cost(&self, opcode: u16) -> i64 {
+        let x = (opcode >> 8 & 0xF) as usize;
+        let vx = self.v_reg[x];
+
+        let cycles = match opcode >> 12 {
+            // a loop over the 256 bytes of the screen
+            0x0 if opcode == 0x00E0 => 1544,
+            0x0 => 10,
+            0x1 => 12,
+            0x2 => 26,
+            0x3 | 0x4 => 10,
+            0x5 | 0x9 => 14,
+            0x6 => 6,
+            0x7 => 10,
+            // the VIP builds and calls a small routine for these
+            0x8 => 44,
+            0xA => 12,
+            0xB => 22,
+            0xC => 36,
+            0xD => self.sprite_cycles(opcode, vx),
+            0xE => 14,
+            _ => match opcode & 0xFF {
+                0x07 | 0x15 | 0x18 => 10,
+                0x0A => 19,
+                0x1E => 16,
+                0x29 => 20,
+                // repeated subtraction, one round per unit of every digit
+                0x33 => 80 + 8 * (vx / 100 + vx / 10 % 10 + vx % 10) as i64,
+                0x55 | 0x65 => 14 + 14 * (x as i64 + 1),
+                _ => 10,
+            },
+        };
+
+        FETCH_CYCLES + cycles
+    }
+
+    // every row is shifted into place bit by bit and written as two bytes
+    // unless it is aligned, rows clipped at the bottom cost nothing
+    fn sprite_cycles(&self, opcode: u16, vx: u8) -> i64 {
+        let vy = self.v_reg[(opcode >> 4 & 0xF) as usize] as usize;
+        let height = self.display.height();
+        let rows = (opcode & 0xF) as usize;
+        let rows = if self.quirks.clip_sprites {
+            rows.min(height - vy % height)
+        } else {
+            rows
+        };
+        let shift = vx as i64 % 8;
+        let row = 16 + 4 * shift + if shift != 0 { 8 } else { 0 };
+
+        26 + rows as i64 * row
+    }
+}
@@ -0,0 +1,281 @@
+use crate::cpu::{extract_nibbles, START_ADDRESS};
+use crate::platform::Platform;
+use std::collections::{BTreeMap, BTreeSet};
+
+// bytes per `db` line in listings
+const DATA_PER_LINE: usize = 8;
+// column the address and bytes comment starts at in listings
+const COMMENT_COLUMN: usize = 28;
+
+/// A line of a disassembled rom, either one instruction or a run of data
+/// bytes that no traced instruction reaches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+    pub label: Option<String>,
+    pub text: String,
+    pub is_code: bool,
+}
+
+/// Mnemonic of an opcode, `None` when it isn't an instruction on `platform`.
+///
+/// `0NNN` is reported as `SYS` even though machine code calls can't be run.
+pub fn mnemonic(opcode: u16, platform: Platform) -> Option<String> {
+    format_instruction(opcode, platform, &|addr| format!("{:#05X}", addr))
+}
+
+/// Splits a rom loaded at `START_ADDRESS` into code and data by following
+/// every jump, call and skip from the entry point.
+///
+/// `BNNN` jump targets depend on V0 and can't be followed, code only
+/// reached through them ends up as data.
+pub fn disassemble(rom: &[u8], platform: Platform) -> Vec<Line> {
+    let start = START_ADDRESS as usize;
+    let end = start + rom.len();
+    let opcode_at = |addr: usize| {
+        if addr >= start && addr + 2 <= end {
+            Some(u16::from_be_bytes([
+                rom[addr - start],
+                rom[addr - start + 1],
+            ]))
+        } else {
+            None
+        }
+    };
+    let size_at = |addr: usize| match opcode_at(addr) {
+        Some(0xF000) if platform.has_xo_opcodes() => 4,
+        _ => 2,
+    };
+
+    // instruction sizes by address
+    let mut code = BTreeMap::new();
+    let mut covered = vec![false; rom.len()];
+    let mut targets = BTreeSet::new();
+    let mut pending = vec![start];
+
+    while let Some(addr) = pending.pop() {
+        let opcode = match opcode_at(addr) {
+            Some(opcode) if mnemonic(opcode, platform).is_some() => opcode,
+            _ => continue,
+        };
+        let size = size_at(addr);
+
+        if addr + size > end || covered[addr - start..addr - start + size].contains(&true) {
+            continue;
+        }
+        covered[addr - start..addr - start + size].fill(true);
+        code.insert(addr, size);
+
+        let next = addr + size;
+        let nnn = (opcode & 0xFFF) as usize;
+
+        match extract_nibbles(opcode) {
+            (0x0, 0x0, 0xE, 0xE) => (),
+            (0x0, 0x0, 0xF, 0xD) if platform.has_schip_opcodes() => (),
+            (0x1, _, _, _) => {
+                targets.insert(nnn);
+                pending.push(nnn);
+            }
+            (0x2, _, _, _) => {
+                targets.insert(nnn);
+                pending.push(nnn);
+                pending.push(next);
+            }
+            (0xA, _, _, _) => {
+                targets.insert(nnn);
+                pending.push(next);
+            }
+            (0xB, _, _, _) => {
+                targets.insert(nnn);
+            }
+            (0xF, 0x0, 0x0, 0x0) if size == 4 => {
+                targets.insert(opcode_at(addr + 2).unwrap() as usize);
+                pending.push(next);
+            }
+            (0x3 | 0x4, _, _, _)
+            | (0x5 | 0x9, _, _, 0x0)
+            | (0xE, _, 0x9, 0xE)
+            | (0xE, _, 0xA, 0x1) => {
+                pending.push(next);
+                pending.push(next + size_at(next));
+            }
+            _ => pending.push(next),
+        }
+    }
+
+    // targets in the middle of an instruction or outside the rom stay numbers
+    let labels: BTreeSet<usize> = targets
+        .into_iter()
+        .filter(|&addr| {
+            addr >= start && addr < end && (code.contains_key(&addr) || !covered[addr - start])
+        })
+        .collect();
+    let name = |addr: u16| {
+        if labels.contains(&(addr as usize)) {
+            label_name(addr)
+        } else {
+            format!("{:#05X}", addr)
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut addr = start;
+
+    while addr < end {
+        let label = labels.contains(&addr).then(|| label_name(addr as u16));
+
+        if let Some(&size) = code.get(&addr) {
+            let bytes = rom[addr - start..addr - start + size].to_vec();
+            let opcode = u16::from_be_bytes([bytes[0], bytes[1]]);
+            let text = if size == 4 {
+                format!(
+                    "LD I, LONG {}",
+                    name(u16::from_be_bytes([bytes[2], bytes[3]]))
+                )
+            } else {
+                format_instruction(opcode, platform, &name).unwrap()
+            };
+
+            lines.push(Line {
+                addr: addr as u16,
+                bytes,
+                label,
+                text,
+                is_code: true,
+            });
+            addr += size;
+        } else {
+            let mut length = 1;
+            while length < DATA_PER_LINE
+                && addr + length < end
+                && !covered[addr + length - start]
+                && !labels.contains(&(addr + length))
+            {
+                length += 1;
+            }
+
+            let bytes = rom[addr - start..addr - start + length].to_vec();
+            let values: Vec<String> = bytes.iter().map(|byte| format!("{:#04X}", byte)).collect();
+
+            lines.push(Line {
+                addr: addr as u16,
+                bytes,
+                label,
+                text: format!("db {}", values.join(", ")),
+                is_code: false,
+            });
+            addr += length;
+        }
+    }
+
+    lines
+}
+
+/// The disassembly as source text, with the address and bytes of every line
+/// in a comment, e.g. `    DRW V0, V1, 5   ; 0x0204: D0 15`.
+pub fn listing(rom: &[u8], platform: Platform) -> String {
+    let mut out = String::new();
+
+    for line in disassemble(rom, platform) {
+        if let Some(label) = &line.label {
+            out.push_str(&format!("{}:\n", label));
+        }
+
+        let bytes: Vec<String> = line
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        out.push_str(&format!(
+            "    {:width$}; {:#06X}: {}\n",
+            line.text,
+            line.addr,
+            bytes.join(" "),
+            width = COMMENT_COLUMN - 4
+        ));
+    }
+
+    out
+}
+
+fn label_name(addr: u16) -> String {
+    format!("L{:03X}", addr)
+}
+
+// `name` formats the addresses of jumps, calls and I loads
+fn format_instruction(
+    opcode: u16,
+    platform: Platform,
+    name: &dyn Fn(u16) -> String,
+) -> Option<String> {
+    let nibbles = extract_nibbles(opcode);
+
+    let x = nibbles.1;
+    let y = nibbles.2;
+    let n = nibbles.3;
+    let nn = opcode & 0xFF;
+    let nnn = opcode & 0xFFF;
+    let schip = platform.has_schip_opcodes();
+    let xo = platform.has_xo_opcodes();
+
+    let text = match nibbles {
+        (0x0, 0x0, 0xC, _) if schip => format!("SCD {}", n),
+        (0x0, 0x0, 0xD, _) if xo => format!("SCU {}", n),
+        (0x0, 0x0, 0xE, 0x0) => "CLS".to_string(),
+        (0x0, 0x0, 0xE, 0xE) => "RET".to_string(),
+        (0x0, 0x0, 0xF, 0xB) if schip => "SCR".to_string(),
+        (0x0, 0x0, 0xF, 0xC) if schip => "SCL".to_string(),
+        (0x0, 0x0, 0xF, 0xD) if schip => "EXIT".to_string(),
+        (0x0, 0x0, 0xF, 0xE) if schip => "LOW".to_string(),
+        (0x0, 0x0, 0xF, 0xF) if schip => "HIGH".to_string(),
+        (0x0, _, _, _) => format!("SYS {:#05X}", nnn),
+        (0x1, _, _, _) => format!("JP {}", name(nnn)),
+        (0x2, _, _, _) => format!("CALL {}", name(nnn)),
+        (0x3, _, _, _) => format!("SE V{:X}, {:#04X}", x, nn),
+        (0x4, _, _, _) => format!("SNE V{:X}, {:#04X}", x, nn),
+        (0x5, _, _, 0x0) => format!("SE V{:X}, V{:X}", x, y),
+        (0x5, _, _, 0x2) if xo => format!("SAVE V{:X}, V{:X}", x, y),
+        (0x5, _, _, 0x3) if xo => format!("LOAD V{:X}, V{:X}", x, y),
+        (0x6, _, _, _) => format!("LD V{:X}, {:#04X}", x, nn),
+        (0x7, _, _, _) => format!("ADD V{:X}, {:#04X}", x, nn),
+        (0x8, _, _, 0x0) => format!("LD V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x1) => format!("OR V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x2) => format!("AND V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x3) => format!("XOR V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x4) => format!("ADD V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x5) => format!("SUB V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x6) => format!("SHR V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x7) => format!("SUBN V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0xE) => format!("SHL V{:X}, V{:X}", x, y),
+        (0x9, _, _, 0x0) => format!("SNE V{:X}, V{:X}", x, y),
+        (0xA, _, _, _) => format!("LD I, {}", name(nnn)),
+        (0xB, _, _, _) => format!("JP V0, {}", name(nnn)),
+        (0xC, _, _, _) => format!("RND V{:X}, {:#04X}", x, nn),
+        (0xD, _, _, _) => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        (0xE, _, 0x9, 0xE) => format!("SKP V{:X}", x),
+        (0xE, _, 0xA, 0x1) => format!("SKNP V{:X}", x),
+        (0xF, 0x0, 0x0, 0x0) if xo => "LD I, LONG".to_string(),
+        (0xF, _, 0x0, 0x1) if xo => format!("PLANE {}", x),
+        (0xF, 0x0, 0x0, 0x2) if xo => "AUDIO".to_string(),
+        (0xF, _, 0x0, 0x7) => format!("LD V{:X}, DT", x),
+        (0xF, _, 0x0, 0xA) => format!("LD V{:X}, K", x),
+        (0xF, _, 0x1, 0x5) => format!("LD DT, V{:X}", x),
+        (0xF, _, 0x1, 0x8) => format!("LD ST, V{:X}", x),
+        (0xF, _, 0x1, 0xE) => format!("ADD I, V{:X}", x),
+        (0xF, _, 0x2, 0x9) => format!("LD F, V{:X}", x),
+        (0xF, _, 0x3, 0x0) if schip => format!("LD HF, V{:X}", x),
+        (0xF, _, 0x3, 0x3) => format!("LD B, V{:X}", x),
+        (0xF, _, 0x3, 0xA) if xo => format!("PITCH V{:X}", x),
+        (0xF, _, 0x5, 0x5) => format!("LD [I], V{:X}", x),
+        (0xF, _, 0x6, 0x5) => format!("LD V{:X}, [I]", x),
+        (0xF, _, 0x7, 0x5) if schip => format!("LD R, V{:X}", x),
+        (0xF, _, 0x8, 0x5) if schip => format!("LD V{:X}, R", x),
+        _ => return None,
+    };
+
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests;
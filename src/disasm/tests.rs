@@ -0,0 +1,103 @@
+use super::*;
+
+#[test]
+fn test_mnemonic() {
+    assert_eq!(
+        mnemonic(0xD015, Platform::Chip8),
+        Some("DRW V0, V1, 5".to_string())
+    );
+    assert_eq!(
+        mnemonic(0x12A0, Platform::Chip8),
+        Some("JP 0x2A0".to_string())
+    );
+    assert_eq!(
+        mnemonic(0x00FF, Platform::Chip8),
+        Some("SYS 0x0FF".to_string())
+    );
+    assert_eq!(
+        mnemonic(0x00FF, Platform::SuperChip),
+        Some("HIGH".to_string())
+    );
+    assert_eq!(mnemonic(0xF002, Platform::SuperChip), None);
+}
+
+#[test]
+fn test_code_and_data() {
+    let rom = [
+        0xA2, 0x08, // LD I, L208
+        0x22, 0x06, // CALL L206
+        0x12, 0x04, // JP L204
+        0x00, 0xEE, // RET
+        0x3C, 0x42, // sprite
+    ];
+
+    let lines = disassemble(&rom, Platform::Chip8);
+    let texts: Vec<(u16, Option<String>, &str, bool)> = lines
+        .iter()
+        .map(|line| {
+            (
+                line.addr,
+                line.label.clone(),
+                line.text.as_str(),
+                line.is_code,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        texts,
+        vec![
+            (0x200, None, "LD I, L208", true),
+            (0x202, None, "CALL L206", true),
+            (0x204, Some("L204".to_string()), "JP L204", true),
+            (0x206, Some("L206".to_string()), "RET", true),
+            (0x208, Some("L208".to_string()), "db 0x3C, 0x42", false),
+        ]
+    );
+}
+
+#[test]
+fn test_skips_follow_both_paths() {
+    // the skipped jump leads to code that isn't reached otherwise
+    let rom = [0x30, 0x01, 0x12, 0x06, 0x00, 0xEE, 0x00, 0xE0, 0x00, 0xEE];
+
+    let lines = disassemble(&rom, Platform::Chip8);
+
+    assert!(lines.iter().all(|line| line.is_code));
+    assert_eq!(lines.len(), 5);
+}
+
+#[test]
+fn test_unreached_bytes_are_data() {
+    // BNNN targets can't be followed
+    let rom = [0xB2, 0x04, 0x00, 0xE0, 0x00, 0xEE];
+
+    let lines = disassemble(&rom, Platform::Chip8);
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].text, "JP V0, L204");
+    assert_eq!(lines[1].text, "db 0x00, 0xE0");
+    assert_eq!(lines[2].label, Some("L204".to_string()));
+    assert_eq!(lines[2].text, "db 0x00, 0xEE");
+}
+
+#[test]
+fn test_xo_long_load() {
+    let rom = [0xF0, 0x00, 0x02, 0x06, 0x00, 0xEE, 0xFF];
+
+    let lines = disassemble(&rom, Platform::XoChip);
+
+    assert_eq!(lines[0].bytes, vec![0xF0, 0x00, 0x02, 0x06]);
+    assert_eq!(lines[0].text, "LD I, LONG L206");
+    assert_eq!(lines[2].text, "db 0xFF");
+}
+
+#[test]
+fn test_listing() {
+    let listing = listing(&[0x12, 0x00], Platform::Chip8);
+
+    assert_eq!(
+        listing,
+        "L200:\n    JP L200                 ; 0x0200: 12 00\n"
+    );
+}
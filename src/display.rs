@@ -0,0 +1,192 @@
+use std::io::{self, Write};
+use std::ops::{Deref, DerefMut};
+
+// every pixel holds one bit per drawing plane, which makes it an index
+// into a 4 color palette
+pub const PLANE_COUNT: usize = 2;
+
+/// RGB colors of the background, both planes and their blend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette(pub [[u8; 3]; 4]);
+
+impl Default for Palette {
+    // the colors of the SDL front end
+    fn default() -> Self {
+        Palette([
+            [49, 57, 66],
+            [216, 222, 233],
+            [191, 97, 106],
+            [235, 203, 139],
+        ])
+    }
+}
+
+pub struct Display {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Display {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear(&mut self, planes: u8) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel &= !planes;
+        }
+    }
+
+    // switching resolution always clears the screen
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.pixels = vec![0; width * height];
+    }
+
+    // returns true when a lit pixel gets turned off
+    pub fn flip(&mut self, x: usize, y: usize, plane: u8) -> bool {
+        let pos = y * self.width + x;
+        let collision = self.pixels[pos] & plane != 0;
+
+        self.pixels[pos] ^= plane;
+
+        collision
+    }
+
+    pub fn scroll_down(&mut self, rows: usize, planes: u8) {
+        let rows = rows.min(self.height);
+
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let above = if y >= rows { self.get(x, y - rows) } else { 0 };
+                self.blend(x, y, above, planes);
+            }
+        }
+    }
+
+    pub fn scroll_up(&mut self, rows: usize, planes: u8) {
+        let rows = rows.min(self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let below = if y + rows < self.height {
+                    self.get(x, y + rows)
+                } else {
+                    0
+                };
+                self.blend(x, y, below, planes);
+            }
+        }
+    }
+
+    pub fn scroll_right(&mut self, columns: usize, planes: u8) {
+        let columns = columns.min(self.width);
+
+        for y in 0..self.height {
+            for x in (0..self.width).rev() {
+                let left = if x >= columns {
+                    self.get(x - columns, y)
+                } else {
+                    0
+                };
+                self.blend(x, y, left, planes);
+            }
+        }
+    }
+
+    pub fn scroll_left(&mut self, columns: usize, planes: u8) {
+        let columns = columns.min(self.width);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let right = if x + columns < self.width {
+                    self.get(x + columns, y)
+                } else {
+                    0
+                };
+                self.blend(x, y, right, planes);
+            }
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    // plain PBM, any lit plane is black
+    pub fn write_pbm(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "P1\n{} {}", self.width, self.height)?;
+
+        for row in self.pixels.chunks(self.width) {
+            let bits: Vec<&str> = row
+                .iter()
+                .map(|pixel| if *pixel == 0 { "0" } else { "1" })
+                .collect();
+            writeln!(out, "{}", bits.join(" "))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the screen as an RGB PNG, every pixel `scale` pixels wide.
+    pub fn write_png(&self, out: impl Write, palette: &Palette, scale: usize) -> io::Result<()> {
+        let width = self.width * scale;
+        let height = self.height * scale;
+        let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut data = Vec::with_capacity(width * height * 3);
+        for row in self.pixels.chunks(self.width) {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|pixel| palette.0[*pixel as usize].repeat(scale))
+                .collect();
+
+            for _ in 0..scale {
+                data.extend_from_slice(&line);
+            }
+        }
+
+        encoder.write_header()?.write_image_data(&data)?;
+
+        Ok(())
+    }
+
+    // copies the selected planes of `value` into a pixel, leaving the others alone
+    fn blend(&mut self, x: usize, y: usize, value: u8, planes: u8) {
+        let pixel = &mut self.pixels[y * self.width + x];
+        *pixel = (*pixel & !planes) | (value & planes);
+    }
+}
+
+impl Deref for Display {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+impl DerefMut for Display {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+}
+
+#[cfg(test)]
+mod tests;
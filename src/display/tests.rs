@@ -0,0 +1,50 @@
+use super::*;
+
+fn decode_png(data: &[u8]) -> (u32, u32, Vec<u8>) {
+    let mut reader = png::Decoder::new(data).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+
+    (info.width, info.height, pixels)
+}
+
+#[test]
+fn test_write_png() {
+    let mut display = Display::new(2, 1);
+    display[1] = 3;
+
+    let mut data = Vec::new();
+    display
+        .write_png(&mut data, &Palette::default(), 1)
+        .unwrap();
+
+    assert_eq!(decode_png(&data), (2, 1, vec![49, 57, 66, 235, 203, 139]));
+}
+
+#[test]
+fn test_write_png_scaled() {
+    let mut display = Display::new(2, 1);
+    display[0] = 1;
+    let palette = Palette([[0; 3], [255; 3], [0; 3], [0; 3]]);
+
+    let mut data = Vec::new();
+    display.write_png(&mut data, &palette, 2).unwrap();
+    let (width, height, pixels) = decode_png(&data);
+
+    assert_eq!((width, height), (4, 2));
+    for row in pixels.chunks(12) {
+        assert_eq!(row, [255, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0]);
+    }
+}
+
+#[test]
+fn test_write_pbm() {
+    let mut display = Display::new(3, 2);
+    display[1] = 2;
+    display[5] = 1;
+
+    let mut data = Vec::new();
+    display.write_pbm(&mut data).unwrap();
+
+    assert_eq!(String::from_utf8(data).unwrap(), "P1\n3 2\n0 1 0\n0 0 1\n");
+}
@@ -0,0 +1,156 @@
+use crate::disasm;
+use crate::platform::Platform;
+use std::fmt;
+
+/// Fault raised by [`Cpu::tick`](crate::Cpu::tick), with the address and
+/// opcode of the instruction that caused it.
+///
+/// The opcode is 0 when fetching the instruction itself failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuError {
+    StackOverflow { pc: u16, opcode: u16 },
+    StackUnderflow { pc: u16, opcode: u16 },
+    MemoryOutOfBounds { pc: u16, opcode: u16, addr: usize },
+    UnknownOpcode { pc: u16, opcode: u16 },
+}
+
+impl CpuError {
+    pub fn pc(&self) -> u16 {
+        match *self {
+            CpuError::StackOverflow { pc, .. }
+            | CpuError::StackUnderflow { pc, .. }
+            | CpuError::MemoryOutOfBounds { pc, .. }
+            | CpuError::UnknownOpcode { pc, .. } => pc,
+        }
+    }
+
+    pub fn opcode(&self) -> u16 {
+        match *self {
+            CpuError::StackOverflow { opcode, .. }
+            | CpuError::StackUnderflow { opcode, .. }
+            | CpuError::MemoryOutOfBounds { opcode, .. }
+            | CpuError::UnknownOpcode { opcode, .. } => opcode,
+        }
+    }
+
+    /// The error followed by the faulting instruction, e.g.
+    /// `0x0204: 0123  SYS 0x123`.
+    pub fn diagnostic(&self, platform: Platform) -> String {
+        let instruction = match self {
+            CpuError::MemoryOutOfBounds { opcode: 0, .. } => "-".to_string(),
+            _ => disasm::mnemonic(self.opcode(), platform).unwrap_or_else(|| "???".to_string()),
+        };
+
+        format!(
+            "{}\n{:#06X}: {:04X}  {}",
+            self,
+            self.pc(),
+            self.opcode(),
+            instruction
+        )
+    }
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CpuError::StackOverflow { .. } => write!(f, "stack overflow")?,
+            CpuError::StackUnderflow { .. } => write!(f, "stack underflow")?,
+            CpuError::MemoryOutOfBounds { addr, .. } => {
+                write!(f, "memory access out of bounds at {:#06X}", addr)?
+            }
+            CpuError::UnknownOpcode { .. } => write!(f, "unknown opcode")?,
+        }
+
+        write!(
+            f,
+            " (pc: {:#06X}, opcode: {:04X})",
+            self.pc(),
+            self.opcode()
+        )
+    }
+}
+
+impl std::error::Error for CpuError {}
+
+/// Reason a save state couldn't be written or restored.
+#[derive(Debug)]
+pub enum StateError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    PlatformMismatch,
+    RomMismatch,
+    Corrupt,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateError::Io(err) => write!(f, "{}", err),
+            StateError::BadMagic => write!(f, "not a save state"),
+            StateError::UnsupportedVersion(version) => {
+                write!(f, "unsupported save state version {}", version)
+            }
+            StateError::PlatformMismatch => write!(f, "save state is for another platform"),
+            StateError::RomMismatch => write!(f, "save state is for another rom"),
+            StateError::Corrupt => write!(f, "save state is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl From<std::io::Error> for StateError {
+    fn from(err: std::io::Error) -> Self {
+        StateError::Io(err)
+    }
+}
+
+/// Reason an input movie couldn't be read or replayed.
+#[derive(Debug)]
+pub enum MovieError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+    RomMismatch,
+    // recorded with random numbers this version can't draw again
+    UnsupportedRng(String),
+    Cpu(CpuError),
+    // the screen after a frame isn't the recorded one
+    Desync { frame: usize },
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MovieError::Io(err) => write!(f, "{}", err),
+            MovieError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            MovieError::RomMismatch => write!(f, "movie is for another rom"),
+            MovieError::UnsupportedRng(name) => {
+                write!(
+                    f,
+                    "movie uses an unsupported random number generator: {}",
+                    name
+                )
+            }
+            MovieError::Cpu(err) => write!(f, "{}", err),
+            MovieError::Desync { frame } => {
+                write!(f, "replay diverged from the recording on frame {}", frame)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MovieError {}
+
+impl From<std::io::Error> for MovieError {
+    fn from(err: std::io::Error) -> Self {
+        MovieError::Io(err)
+    }
+}
+
+impl From<CpuError> for MovieError {
+    fn from(err: CpuError) -> Self {
+        MovieError::Cpu(err)
+    }
+}
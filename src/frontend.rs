@@ -0,0 +1,7 @@
+pub mod beeper;
+pub mod capture;
+pub mod cli;
+pub mod config;
+pub mod debugger;
+pub mod keymap;
+pub mod osd;
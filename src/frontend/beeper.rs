@@ -0,0 +1,72 @@
+use chip_8::audio::Tone;
+use chip_8::Cpu;
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+
+const SAMPLE_RATE: i32 = 44100;
+
+struct ToneCallback {
+    tone: Tone,
+    sample_rate: u32,
+}
+
+impl AudioCallback for ToneCallback {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        self.tone.fill(out, self.sample_rate);
+    }
+}
+
+pub struct Beeper {
+    device: AudioDevice<ToneCallback>,
+    muted: bool,
+    use_pattern: bool,
+}
+
+impl Beeper {
+    // use_pattern plays the XO-CHIP audio pattern instead of the tone's waveform
+    pub fn new(sdl_ctx: &sdl2::Sdl, tone: Tone, use_pattern: bool) -> Result<Self, String> {
+        let audio_subsystem = sdl_ctx.audio()?;
+        let desired_spec = AudioSpecDesired {
+            freq: Some(SAMPLE_RATE),
+            channels: Some(1),
+            samples: None,
+        };
+
+        let device = audio_subsystem.open_playback(None, &desired_spec, |spec| ToneCallback {
+            tone,
+            sample_rate: spec.freq as u32,
+        })?;
+        device.resume();
+
+        Ok(Self {
+            device,
+            muted: false,
+            use_pattern,
+        })
+    }
+
+    // returns whether it is muted now
+    pub fn toggle_mute(&mut self) -> bool {
+        self.muted = !self.muted;
+        self.muted
+    }
+
+    pub fn silence(&mut self) {
+        self.device.lock().tone.set_active(false);
+    }
+
+    // called once per frame to follow the sound timer
+    pub fn update(&mut self, cpu: &Cpu) {
+        let active = cpu.is_beeping() && !self.muted;
+        let mut callback = self.device.lock();
+
+        if self.use_pattern {
+            callback
+                .tone
+                .set_pattern(Some((*cpu.audio_pattern(), cpu.playback_rate())));
+        }
+
+        callback.tone.set_active(active);
+    }
+}
@@ -0,0 +1,95 @@
+use chip_8::display::{Display, Palette};
+use chip_8::record::GifRecorder;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// size of a recorded high resolution pixel
+const RECORDING_SCALE: usize = 2;
+
+pub type Recorder = GifRecorder<BufWriter<File>>;
+
+/// Saves the screen as a PNG next to the rom, named after it and the time,
+/// e.g. `pong-20240131-174502.png`.
+pub fn screenshot(
+    display: &Display,
+    palette: &Palette,
+    scale: usize,
+    rom_path: &Path,
+) -> io::Result<PathBuf> {
+    let path = output_path(rom_path, "png");
+    let file = File::create(&path)?;
+
+    display.write_png(BufWriter::new(file), palette, scale)?;
+
+    Ok(path)
+}
+
+/// Starts recording a GIF next to the rom, named like screenshots.
+pub fn start_recording(palette: &Palette, rom_path: &Path) -> io::Result<(Recorder, PathBuf)> {
+    let path = output_path(rom_path, "gif");
+    let file = File::create(&path)?;
+
+    Ok((
+        GifRecorder::new(BufWriter::new(file), palette, RECORDING_SCALE)?,
+        path,
+    ))
+}
+
+fn output_path(rom_path: &Path, extension: &str) -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    unique_path(rom_path, &timestamp(seconds), extension)
+}
+
+// captures taken within the same second get `-2`, `-3` and so on
+fn unique_path(rom_path: &Path, timestamp: &str, extension: &str) -> PathBuf {
+    let name = rom_path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut path = rom_path.with_file_name(format!("{}-{}.{}", name, timestamp, extension));
+    let mut count = 1;
+
+    while path.exists() {
+        count += 1;
+        path = rom_path.with_file_name(format!("{}-{}-{}.{}", name, timestamp, count, extension));
+    }
+
+    path
+}
+
+// UTC, there is no portable way to get the local time zone without a crate
+fn timestamp(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    let time = seconds % 86400;
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+// days since 1970-01-01 to a date, from Howard Hinnant's date algorithms
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests;
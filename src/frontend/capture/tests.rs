@@ -0,0 +1,33 @@
+use super::*;
+
+#[test]
+fn test_civil_from_days() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    assert_eq!(civil_from_days(11016), (2000, 2, 29));
+    assert_eq!(civil_from_days(19753), (2024, 1, 31));
+}
+
+#[test]
+fn test_timestamp() {
+    assert_eq!(timestamp(0), "19700101-000000");
+    assert_eq!(timestamp(1706723102), "20240131-174502");
+}
+
+#[test]
+fn test_unique_path() {
+    let dir = std::env::temp_dir().join(format!("chip8-capture-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let rom_path = dir.join("pong.ch8");
+
+    let first = unique_path(&rom_path, "20240131-174502", "png");
+    std::fs::write(&first, b"").unwrap();
+    let second = unique_path(&rom_path, "20240131-174502", "png");
+    std::fs::write(&second, b"").unwrap();
+    let third = unique_path(&rom_path, "20240131-174502", "png");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(first, dir.join("pong-20240131-174502.png"));
+    assert_eq!(second, dir.join("pong-20240131-174502-2.png"));
+    assert_eq!(third, dir.join("pong-20240131-174502-3.png"));
+}
@@ -0,0 +1,172 @@
+use super::config::{parse_color, Settings};
+use chip_8::audio::Waveform;
+use chip_8::{Platform, Quirks, Timing, UnknownOpcodePolicy};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+usage: chip-8 [options] <path to rom> [quirks]
+
+options:
+    --platform <name>     chip8, schip or xochip, instead of the rom extension
+    --quirks <preset>     vip, chip48, schip or xochip, the same as [quirks]
+    --quirk <name=value>  change one quirk, e.g. --quirk shift_uses_vy=1
+    --ipf <count>         instructions per frame, 10 by default
+    --timing <name>       ipf, or vip to run as fast as the COSMAC VIP
+    --unknown-opcodes <policy>
+                          halt, ignore, or log to print them and go on
+    --scale <factor>      size of a high resolution pixel, 4 by default
+    --fg <color>          foreground color as RRGGBB
+    --bg <color>          background color as RRGGBB
+    --seed <number>       seed the random numbers of CXNN
+    --record <path>       save the input to a movie for chip8-headless --movie
+    --mute                start without sound
+    --fullscreen          start in fullscreen
+    --fps                 show frames and instructions per second
+    --rewind <seconds>    how far back rewinding goes, 10 by default
+    --waveform <name>     square, sine, triangle or sawtooth beep
+    --frequency <hertz>   pitch of the beep, 440 by default
+    --volume <level>      from 0.0 to 1.0, 0.1 by default
+    --start-paused        start paused in the debugger, also --debug
+    --headless            run without a window until the rom exits
+    --frames <count>      stop a headless run after this many frames
+    -h, --help            show this help
+    -V, --version         show the version";
+
+/// What the command line asks for.
+#[derive(Debug, PartialEq)]
+pub enum Cli {
+    Run(Box<Options>),
+    Help,
+    Version,
+}
+
+/// The options of a run, `settings` go on top of the config file.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub rom_path: PathBuf,
+    pub settings: Settings,
+    pub seed: Option<u64>,
+    pub movie_path: Option<PathBuf>,
+    pub start_paused: bool,
+    pub headless: bool,
+    pub frames: Option<u64>,
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, String> {
+    let mut args = args.into_iter();
+    let mut positional = Vec::new();
+    let mut options = Options {
+        rom_path: PathBuf::new(),
+        settings: Settings::default(),
+        seed: None,
+        movie_path: None,
+        start_paused: false,
+        headless: false,
+        frames: None,
+    };
+    let settings = &mut options.settings;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Cli::Help),
+            "-V" | "--version" => return Ok(Cli::Version),
+            "--platform" => {
+                let name = value()?;
+                settings.platform =
+                    Some(Platform::from_name(&name).ok_or(format!("unknown platform: {}", name))?);
+            }
+            "--quirks" => settings.quirks = Some(parse_preset(&value()?)?),
+            "--quirk" => {
+                let quirk = value()?;
+                let (name, value) = quirk
+                    .split_once('=')
+                    .ok_or(format!("expected name=value: {}", quirk))?;
+
+                Quirks::default().set(name, value)?;
+                settings.quirk.push((name.to_string(), value.to_string()));
+            }
+            "--ipf" => settings.ticks_per_frame = Some(parse_count(&arg, &value()?)?),
+            "--timing" => {
+                let name = value()?;
+                settings.timing =
+                    Some(Timing::from_name(&name).ok_or(format!("unknown timing: {}", name))?);
+            }
+            "--unknown-opcodes" => {
+                let name = value()?;
+                settings.unknown_opcodes = Some(
+                    UnknownOpcodePolicy::from_name(&name)
+                        .ok_or(format!("unknown opcode policy: {}", name))?,
+                );
+            }
+            "--scale" => settings.scale = Some(parse_count(&arg, &value()?)?),
+            "--fg" => settings.fg = Some(parse_color(&value()?)?),
+            "--bg" => settings.bg = Some(parse_color(&value()?)?),
+            "--seed" => {
+                let seed = value()?;
+                options.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("invalid seed: {}", seed))?,
+                );
+            }
+            "--record" => options.movie_path = Some(PathBuf::from(value()?)),
+            "--mute" => settings.mute = Some(true),
+            "--fullscreen" => settings.fullscreen = Some(true),
+            "--fps" => settings.counter = Some(true),
+            "--rewind" => settings.rewind = Some(parse_number(&arg, &value()?)?),
+            "--waveform" => {
+                let name = value()?;
+                settings.waveform =
+                    Some(Waveform::from_name(&name).ok_or(format!("unknown waveform: {}", name))?);
+            }
+            "--frequency" => settings.frequency = Some(parse_number(&arg, &value()?)?),
+            "--volume" => settings.volume = Some(parse_number(&arg, &value()?)?),
+            "--start-paused" | "--debug" => options.start_paused = true,
+            "--headless" => options.headless = true,
+            "--frames" => options.frames = Some(parse_count(&arg, &value()?)?),
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option: {}", arg))
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    options.rom_path = positional
+        .next()
+        .map(PathBuf::from)
+        .ok_or("missing path to rom file")?;
+    if let Some(name) = positional.next() {
+        options.settings.quirks = Some(parse_preset(&name)?);
+    }
+    if let Some(arg) = positional.next() {
+        return Err(format!("unexpected argument: {}", arg));
+    }
+
+    Ok(Cli::Run(Box::new(options)))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", option, value))
+}
+
+// counts start at 1
+fn parse_count<T: std::str::FromStr + Default + PartialEq>(
+    option: &str,
+    value: &str,
+) -> Result<T, String> {
+    match parse_number(option, value)? {
+        count if count != T::default() => Ok(count),
+        _ => Err(format!("invalid value for {}: {}", option, value)),
+    }
+}
+
+fn parse_preset(name: &str) -> Result<Quirks, String> {
+    Quirks::from_name(name).ok_or(format!("unknown quirks preset: {}", name))
+}
+
+#[cfg(test)]
+mod tests;
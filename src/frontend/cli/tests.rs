@@ -0,0 +1,92 @@
+use super::*;
+
+fn parse_args(args: &str) -> Result<Cli, String> {
+    parse(args.split_whitespace().map(str::to_string))
+}
+
+fn options(args: &str) -> Options {
+    match parse_args(args) {
+        Ok(Cli::Run(options)) => *options,
+        other => panic!("not a run: {:?}", other),
+    }
+}
+
+#[test]
+fn test_defaults() {
+    let options = options("pong.sc8");
+
+    assert_eq!(options.rom_path, PathBuf::from("pong.sc8"));
+    assert_eq!(options.settings, Settings::default());
+    assert_eq!(
+        options.settings.platform(&options.rom_path),
+        Platform::SuperChip
+    );
+    assert!(!options.headless && !options.start_paused);
+}
+
+#[test]
+fn test_options() {
+    let options = options(
+        "--platform xochip --ipf 30 --timing vip --scale 2 --fg #ff8000 --bg 000000 --seed 5 \
+         --mute --fullscreen --fps --debug --headless --frames 100 --unknown-opcodes ignore \
+         --rewind 0 --waveform triangle --frequency 261.6 --volume 0.25 game.ch8",
+    );
+
+    let settings = &options.settings;
+
+    assert_eq!(settings.platform, Some(Platform::XoChip));
+    assert_eq!(settings.ticks_per_frame, Some(30));
+    assert_eq!(settings.timing, Some(Timing::CosmacVip));
+    assert_eq!(settings.scale, Some(2));
+    assert_eq!(settings.palette().0[..2], [[0, 0, 0], [255, 128, 0]]);
+    assert_eq!(settings.mute, Some(true));
+    assert_eq!(settings.fullscreen, Some(true));
+    assert_eq!(settings.counter, Some(true));
+    assert_eq!(settings.unknown_opcodes, Some(UnknownOpcodePolicy::Ignore));
+    assert_eq!(settings.rewind, Some(0));
+    assert_eq!(settings.waveform, Some(Waveform::Triangle));
+    assert_eq!(settings.frequency, Some(261.6));
+    assert_eq!(settings.volume, Some(0.25));
+    assert_eq!(options.seed, Some(5));
+    assert!(options.start_paused && options.headless);
+    assert_eq!(options.frames, Some(100));
+}
+
+#[test]
+fn test_quirks() {
+    let mut expected = Quirks::COSMAC_VIP;
+    expected.jump_uses_vx = true;
+
+    let quirks = |args| options(args).settings.quirks(Platform::Chip8).unwrap();
+
+    assert_eq!(quirks("--quirk jump_uses_vx=1 game.ch8 vip"), expected);
+    assert_eq!(quirks("--quirks chip48 game.ch8"), Quirks::CHIP_48);
+}
+
+#[test]
+fn test_help_and_version() {
+    assert_eq!(parse_args("game.ch8 --help"), Ok(Cli::Help));
+    assert_eq!(parse_args("-V"), Ok(Cli::Version));
+}
+
+#[test]
+fn test_errors() {
+    let error = |args| parse_args(args).unwrap_err();
+
+    assert_eq!(error(""), "missing path to rom file");
+    assert_eq!(error("--ipf"), "--ipf needs a value");
+    assert_eq!(error("--ipf 0 game.ch8"), "invalid value for --ipf: 0");
+    assert_eq!(error("--fg red game.ch8"), "invalid color: red");
+    assert_eq!(error("--timing pal game.ch8"), "unknown timing: pal");
+    assert_eq!(error("--quirk fast=1 game.ch8"), "unknown quirk: fast");
+    assert_eq!(
+        error("--unknown-opcodes skip game.ch8"),
+        "unknown opcode policy: skip"
+    );
+    assert_eq!(
+        error("--volume loud game.ch8"),
+        "invalid value for --volume: loud"
+    );
+    assert_eq!(error("--loud game.ch8"), "unknown option: --loud");
+    assert_eq!(error("game.ch8 vip extra"), "unexpected argument: extra");
+}
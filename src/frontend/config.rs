@@ -0,0 +1,302 @@
+use super::keymap::{button_from_name, keypad_key, scancode_from_name};
+use chip_8::audio::Waveform;
+use chip_8::display::Palette;
+use chip_8::{Platform, Quirks, Timing, UnknownOpcodePolicy};
+use sdl2::controller::Button;
+use sdl2::keyboard::Scancode;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Settings a config file, a rom section or the command line can change,
+/// `None` leaves the setting to the layer below.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub platform: Option<Platform>,
+    // quirks preset
+    pub quirks: Option<Quirks>,
+    // single quirks applied on top of the preset, in order
+    pub quirk: Vec<(String, String)>,
+    pub ticks_per_frame: Option<usize>,
+    pub timing: Option<Timing>,
+    pub unknown_opcodes: Option<UnknownOpcodePolicy>,
+    pub scale: Option<u32>,
+    pub fg: Option<[u8; 3]>,
+    pub bg: Option<[u8; 3]>,
+    pub mute: Option<bool>,
+    pub fullscreen: Option<bool>,
+    // frames and instructions per second in the corner
+    pub counter: Option<bool>,
+    // seconds of gameplay that can be rewound, 0 turns rewinding off
+    pub rewind: Option<usize>,
+    // the beep
+    pub waveform: Option<Waveform>,
+    pub frequency: Option<f32>,
+    pub volume: Option<f32>,
+    // host keys and controller buttons for single keypad keys, replacing the
+    // default ones
+    pub keys: BTreeMap<usize, Vec<Scancode>>,
+    pub buttons: BTreeMap<usize, Vec<Button>>,
+}
+
+impl Settings {
+    /// Puts `other` on top of these settings.
+    pub fn merge(&mut self, other: Settings) {
+        self.platform = other.platform.or(self.platform);
+        // single quirks only change the preset of their own layer or below
+        if other.quirks.is_some() {
+            self.quirk.clear();
+        }
+        self.quirks = other.quirks.or(self.quirks);
+        self.quirk.extend(other.quirk);
+        self.ticks_per_frame = other.ticks_per_frame.or(self.ticks_per_frame);
+        self.timing = other.timing.or(self.timing);
+        self.unknown_opcodes = other.unknown_opcodes.or(self.unknown_opcodes);
+        self.scale = other.scale.or(self.scale);
+        self.fg = other.fg.or(self.fg);
+        self.bg = other.bg.or(self.bg);
+        self.mute = other.mute.or(self.mute);
+        self.fullscreen = other.fullscreen.or(self.fullscreen);
+        self.counter = other.counter.or(self.counter);
+        self.rewind = other.rewind.or(self.rewind);
+        self.waveform = other.waveform.or(self.waveform);
+        self.frequency = other.frequency.or(self.frequency);
+        self.volume = other.volume.or(self.volume);
+        self.keys.extend(other.keys);
+        self.buttons.extend(other.buttons);
+    }
+
+    // the extension picks the platform unless it is set
+    pub fn platform(&self, rom_path: &Path) -> Platform {
+        self.platform
+            .unwrap_or_else(|| Platform::from_path(rom_path))
+    }
+
+    // the preset of the platform unless another is set, then single quirks
+    pub fn quirks(&self, platform: Platform) -> Result<Quirks, String> {
+        let mut quirks = self.quirks.unwrap_or_else(|| platform.quirks());
+
+        for (name, value) in &self.quirk {
+            quirks.set(name, value)?;
+        }
+
+        Ok(quirks)
+    }
+
+    pub fn palette(&self) -> Palette {
+        let mut palette = Palette::default();
+        palette.0[0] = self.bg.unwrap_or(palette.0[0]);
+        palette.0[1] = self.fg.unwrap_or(palette.0[1]);
+
+        palette
+    }
+}
+
+/// The config file, defaults for every rom and sections for single roms.
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    pub default: Settings,
+    // by the SHA-1 of the rom in lowercase hexadecimal
+    pub roms: BTreeMap<String, Settings>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let file: File =
+            toml::from_str(text).map_err(|err| err.to_string().trim_end().to_string())?;
+
+        let mut roms = BTreeMap::new();
+        for (hash, section) in file.rom {
+            let settings = section
+                .into_settings()
+                .map_err(|err| format!("[rom.{}]: {}", hash, err))?;
+            roms.insert(hash.to_lowercase(), settings);
+        }
+
+        Ok(Self {
+            default: file
+                .default
+                .into_settings()
+                .map_err(|err| format!("[default]: {}", err))?,
+            roms,
+        })
+    }
+
+    /// Reads `CHIP8_CONFIG` or `chip-8/config.toml` in the user's config
+    /// directory, a missing file is an empty config.
+    pub fn load() -> Result<Self, String> {
+        let path = match std::env::var_os("CHIP8_CONFIG") {
+            Some(path) => PathBuf::from(path),
+            None => match dirs::config_dir() {
+                Some(dir) => dir.join("chip-8").join("config.toml"),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|err| format!("{}: {}", path.display(), err)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(format!("can't read {}: {}", path.display(), err)),
+        }
+    }
+
+    /// The defaults with the section of the rom, if it has one, on top.
+    pub fn settings(&self, rom: &[u8]) -> Settings {
+        let hash = sha1_smol::Sha1::from(rom).digest().to_string();
+        let mut settings = self.default.clone();
+
+        if let Some(rom) = self.roms.get(&hash) {
+            settings.merge(rom.clone());
+        }
+
+        settings
+    }
+}
+
+// the file as written, checked and converted by `into_settings`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default)]
+    default: RawSettings,
+    #[serde(default)]
+    rom: BTreeMap<String, RawSettings>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSettings {
+    // only there to tell rom sections apart
+    #[allow(dead_code)]
+    name: Option<String>,
+    platform: Option<String>,
+    quirks: Option<String>,
+    #[serde(default)]
+    quirk: BTreeMap<String, toml::Value>,
+    ipf: Option<usize>,
+    timing: Option<String>,
+    unknown_opcodes: Option<String>,
+    scale: Option<u32>,
+    fg: Option<String>,
+    bg: Option<String>,
+    mute: Option<bool>,
+    fullscreen: Option<bool>,
+    fps: Option<bool>,
+    rewind: Option<usize>,
+    waveform: Option<String>,
+    frequency: Option<f32>,
+    volume: Option<f32>,
+    #[serde(default)]
+    keys: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    buttons: BTreeMap<String, toml::Value>,
+}
+
+impl RawSettings {
+    fn into_settings(self) -> Result<Settings, String> {
+        if self.ipf == Some(0) || self.scale == Some(0) {
+            return Err("ipf and scale must be at least 1".to_string());
+        }
+
+        let mut quirk = Vec::new();
+        for (name, value) in self.quirk {
+            let value = match value {
+                toml::Value::Boolean(flag) => (flag as u8).to_string(),
+                toml::Value::String(text) => text,
+                value => value.to_string(),
+            };
+
+            // checked here so mistakes show up before a rom needs them
+            Quirks::default().set(&name, &value)?;
+            quirk.push((name, value));
+        }
+
+        Ok(Settings {
+            platform: self
+                .platform
+                .map(|name| Platform::from_name(&name).ok_or(format!("unknown platform: {}", name)))
+                .transpose()?,
+            quirks: self
+                .quirks
+                .map(|name| {
+                    Quirks::from_name(&name).ok_or(format!("unknown quirks preset: {}", name))
+                })
+                .transpose()?,
+            quirk,
+            ticks_per_frame: self.ipf,
+            timing: self
+                .timing
+                .map(|name| Timing::from_name(&name).ok_or(format!("unknown timing: {}", name)))
+                .transpose()?,
+            unknown_opcodes: self
+                .unknown_opcodes
+                .map(|name| {
+                    UnknownOpcodePolicy::from_name(&name)
+                        .ok_or(format!("unknown opcode policy: {}", name))
+                })
+                .transpose()?,
+            scale: self.scale,
+            fg: self.fg.map(|color| parse_color(&color)).transpose()?,
+            bg: self.bg.map(|color| parse_color(&color)).transpose()?,
+            mute: self.mute,
+            fullscreen: self.fullscreen,
+            counter: self.fps,
+            rewind: self.rewind,
+            waveform: self
+                .waveform
+                .map(|name| Waveform::from_name(&name).ok_or(format!("unknown waveform: {}", name)))
+                .transpose()?,
+            frequency: self.frequency,
+            volume: self.volume,
+            keys: bindings(self.keys, scancode_from_name)?,
+            buttons: bindings(self.buttons, button_from_name)?,
+        })
+    }
+}
+
+// `5 = "W"` or `5 = ["W", "Up"]`, an empty list leaves the key unbound
+fn bindings<T>(
+    table: BTreeMap<String, toml::Value>,
+    from_name: fn(&str) -> Result<T, String>,
+) -> Result<BTreeMap<usize, Vec<T>>, String> {
+    let mut bindings = BTreeMap::new();
+
+    for (key, value) in table {
+        let names = match value {
+            toml::Value::String(name) => vec![toml::Value::String(name)],
+            toml::Value::Array(names) => names,
+            value => return Err(format!("expected names for key {}: {}", key, value)),
+        };
+        let inputs = names
+            .iter()
+            .map(|name| match name {
+                toml::Value::String(name) => from_name(name),
+                name => Err(format!("expected a name: {}", name)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        bindings.insert(keypad_key(&key)?, inputs);
+    }
+
+    Ok(bindings)
+}
+
+/// `RRGGBB`, optionally with a leading `#`.
+pub fn parse_color(text: &str) -> Result<[u8; 3], String> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    let invalid = || format!("invalid color: {}", text);
+
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut color = [0; 3];
+    for (index, channel) in color.iter_mut().enumerate() {
+        *channel = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests;
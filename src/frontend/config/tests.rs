@@ -0,0 +1,179 @@
+use super::*;
+
+const CONFIG: &str = r##"
+[default]
+ipf = 15
+timing = "vip"
+fg = "#ffcc00"
+mute = true
+unknown_opcodes = "log"
+rewind = 30
+waveform = "sine"
+frequency = 220
+volume = 0.5
+
+[rom.A9993E364706816ABA3E25717850C26C9CD0D89D]
+name = "abc"
+platform = "schip"
+quirks = "vip"
+scale = 8
+quirk = { jump_uses_vx = true, memory_increment = "x" }
+"##;
+
+#[test]
+fn test_parse() {
+    let config = Config::parse(CONFIG).unwrap();
+
+    assert_eq!(config.default.ticks_per_frame, Some(15));
+    assert_eq!(config.default.timing, Some(Timing::CosmacVip));
+    assert_eq!(config.default.fg, Some([0xff, 0xcc, 0x00]));
+    assert_eq!(config.default.mute, Some(true));
+    assert_eq!(
+        config.default.unknown_opcodes,
+        Some(UnknownOpcodePolicy::Log)
+    );
+    assert_eq!(config.default.rewind, Some(30));
+    assert_eq!(config.default.waveform, Some(Waveform::Sine));
+    assert_eq!(config.default.frequency, Some(220.0));
+    assert_eq!(config.default.volume, Some(0.5));
+
+    let rom = &config.roms["a9993e364706816aba3e25717850c26c9cd0d89d"];
+    assert_eq!(rom.platform, Some(Platform::SuperChip));
+    assert_eq!(rom.quirks, Some(Quirks::COSMAC_VIP));
+    assert_eq!(rom.scale, Some(8));
+    assert_eq!(
+        rom.quirk,
+        [
+            ("jump_uses_vx".to_string(), "1".to_string()),
+            ("memory_increment".to_string(), "x".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_settings_for_rom() {
+    let config = Config::parse(CONFIG).unwrap();
+
+    // "abc" has the SHA-1 of the section
+    let settings = config.settings(b"abc");
+    assert_eq!(settings.ticks_per_frame, Some(15));
+    assert_eq!(settings.scale, Some(8));
+    assert_eq!(settings.platform(Path::new("abc.ch8")), Platform::SuperChip);
+
+    let mut expected = Quirks::COSMAC_VIP;
+    expected.jump_uses_vx = true;
+    expected.memory_increment = chip_8::quirks::MemoryIncrement::ByX;
+    assert_eq!(settings.quirks(Platform::SuperChip), Ok(expected));
+
+    assert_eq!(config.settings(b"other"), config.default);
+}
+
+#[test]
+fn test_merge() {
+    let mut settings = Config::parse(CONFIG).unwrap().settings(b"abc");
+    settings.merge(Settings {
+        ticks_per_frame: Some(30),
+        mute: Some(false),
+        rewind: Some(0),
+        quirk: vec![("jump_uses_vx".to_string(), "0".to_string())],
+        ..Settings::default()
+    });
+
+    assert_eq!(settings.ticks_per_frame, Some(30));
+    assert_eq!(settings.scale, Some(8));
+    assert_eq!(settings.mute, Some(false));
+    assert_eq!(settings.rewind, Some(0));
+    assert_eq!(settings.waveform, Some(Waveform::Sine));
+    assert!(!settings.quirks(Platform::Chip8).unwrap().jump_uses_vx);
+}
+
+#[test]
+fn test_preset_replaces_lower_quirks() {
+    let config = Config::parse(
+        "[default.quirk]\nshift_uses_vy = false\n\
+         [rom.a9993e364706816aba3e25717850c26c9cd0d89d]\nquirks = \"vip\"",
+    )
+    .unwrap();
+
+    // a preset in the rom section
+    assert_eq!(
+        config.settings(b"abc").quirks(Platform::Chip8),
+        Ok(Quirks::COSMAC_VIP)
+    );
+
+    // a preset on the command line
+    let mut settings = config.settings(b"other");
+    settings.merge(Settings {
+        quirks: Some(Quirks::COSMAC_VIP),
+        ..Settings::default()
+    });
+    assert_eq!(settings.quirks(Platform::Chip8), Ok(Quirks::COSMAC_VIP));
+
+    // without a preset they still apply
+    let mut settings = config.settings(b"other");
+    settings.merge(Settings::default());
+    assert!(!settings.quirks(Platform::XoChip).unwrap().shift_uses_vy);
+}
+
+#[test]
+fn test_errors() {
+    let error = |text| Config::parse(text).unwrap_err();
+
+    assert!(error("[default]\nspeed = 3").contains("unknown field `speed`"));
+    assert_eq!(
+        error("[default]\nplatform = \"nes\""),
+        "[default]: unknown platform: nes"
+    );
+    assert_eq!(
+        error("[rom.abc]\nbg = \"black\""),
+        "[rom.abc]: invalid color: black"
+    );
+    assert_eq!(
+        error("[rom.abc.quirk]\nfast = true"),
+        "[rom.abc]: unknown quirk: fast"
+    );
+    assert_eq!(
+        error("[default]\nwaveform = \"noise\""),
+        "[default]: unknown waveform: noise"
+    );
+    assert_eq!(
+        error("[default]\nipf = 0"),
+        "[default]: ipf and scale must be at least 1"
+    );
+}
+
+#[test]
+fn test_parse_color() {
+    assert_eq!(parse_color("#102030"), Ok([0x10, 0x20, 0x30]));
+    assert_eq!(parse_color("a0b0c0"), Ok([0xa0, 0xb0, 0xc0]));
+    assert!(parse_color("#12345").is_err());
+}
+
+#[test]
+fn test_bindings() {
+    let config = Config::parse(
+        "[default.keys]\n5 = [\"W\", \"Up\"]\nA = \"Space\"\n\
+         [default.buttons]\n5 = \"dpup\"\n8 = []",
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.default.keys,
+        BTreeMap::from([
+            (0x5, vec![Scancode::W, Scancode::Up]),
+            (0xA, vec![Scancode::Space]),
+        ])
+    );
+    assert_eq!(
+        config.default.buttons,
+        BTreeMap::from([(0x5, vec![Button::DPadUp]), (0x8, vec![])])
+    );
+    assert_eq!(
+        Config::parse("[default.keys]\nG = \"W\"").unwrap_err(),
+        "[default]: invalid keypad key: G"
+    );
+    assert_eq!(
+        Config::parse("[default.buttons]\n1 = \"select\"").unwrap_err(),
+        "[default]: unknown button: select"
+    );
+}
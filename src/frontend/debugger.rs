@@ -0,0 +1,201 @@
+use chip_8::mem::{Access, AccessKind, Watchpoint};
+use chip_8::{disasm, Cpu};
+use std::io::BufRead;
+use std::sync::mpsc::{self, Receiver};
+
+// instructions listed before and after the program counter
+const CONTEXT: u16 = 4;
+// stack entries on one line of the panel
+const STACK_PER_LINE: usize = 8;
+// accesses kept between two frames while tracing
+const TRACE_LIMIT: usize = 4096;
+
+pub enum Command {
+    Continue,
+    Pause,
+    Step,
+    StepFrame,
+    Break(u16),
+    Delete(u16),
+    Watch(Watchpoint),
+    Unwatch(u16),
+    Trace,
+    Show,
+}
+
+impl Command {
+    // commands typed on the terminal, e.g. `b 2a0` or `step`
+    fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let mut next = || words.next().ok_or("missing address");
+        let addr = |word: &str| {
+            let digits = word.trim_start_matches("0x");
+            u16::from_str_radix(digits, 16).map_err(|_| format!("invalid address: {}", word))
+        };
+
+        match name {
+            "c" | "continue" => Ok(Command::Continue),
+            "p" | "pause" => Ok(Command::Pause),
+            "s" | "step" => Ok(Command::Step),
+            "f" | "frame" => Ok(Command::StepFrame),
+            "b" | "break" => Ok(Command::Break(addr(next()?)?)),
+            "d" | "delete" => Ok(Command::Delete(addr(next()?)?)),
+            "w" | "watch" => {
+                // `w 2a0-2a3 rw`, a single address and writes by default
+                let range = next()?;
+                let (start, end) = range.split_once('-').unwrap_or((range, range));
+                let kinds = next().unwrap_or("w");
+
+                Ok(Command::Watch(Watchpoint {
+                    start: addr(start)?,
+                    end: addr(end)?,
+                    read: kinds.contains('r'),
+                    write: kinds.contains('w'),
+                    execute: kinds.contains('x'),
+                }))
+            }
+            "dw" | "unwatch" => Ok(Command::Unwatch(addr(next()?)?)),
+            "t" | "trace" => Ok(Command::Trace),
+            "r" | "regs" => Ok(Command::Show),
+            _ => Err(format!("unknown command: {}", name)),
+        }
+    }
+}
+
+pub struct Debugger {
+    paused: bool,
+    lines: Receiver<String>,
+}
+
+impl Debugger {
+    pub fn new(paused: bool) -> Self {
+        let (sender, lines) = mpsc::channel();
+
+        // stdin can only be read blocking, so it gets its own thread
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { paused, lines }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self, cpu: &Cpu) {
+        self.paused = true;
+        print_panel(cpu);
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    // commands typed since the last call
+    pub fn poll(&mut self) -> Vec<Command> {
+        self.lines
+            .try_iter()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                Command::parse(&line)
+                    .map_err(|err| eprintln!("{}", err))
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+// starts tracing memory accesses or stops it, returns whether it's on
+pub fn toggle_trace(cpu: &mut Cpu) -> bool {
+    let memory = cpu.memory_mut();
+
+    if memory.is_tracing() {
+        memory.stop_trace();
+    } else {
+        memory.start_trace(TRACE_LIMIT);
+    }
+
+    memory.is_tracing()
+}
+
+// e.g. `0x0204: F355  write 0x0300 = 0x01`
+pub fn format_access(access: &Access) -> String {
+    let kind = match access.kind {
+        AccessKind::Read => "read",
+        AccessKind::Write => "write",
+        AccessKind::Execute => "execute",
+    };
+
+    format!(
+        "{:#06X}: {:04X}  {} {:#06X} = {:#04X}",
+        access.pc, access.opcode, kind, access.addr, access.value
+    )
+}
+
+pub fn print_panel(cpu: &Cpu) {
+    for line in panel(cpu) {
+        println!("{}", line);
+    }
+}
+
+/// The registers, the stack and the instructions around the program counter,
+/// in lines narrow enough for the window's overlay.
+pub fn panel(cpu: &Cpu) -> Vec<String> {
+    let registers = cpu.registers();
+    let mut lines = vec![format!(
+        "PC: {:#06X}  I: {:#06X}  SP: {}  DT: {:#04X}  ST: {:#04X}",
+        registers.pc, registers.i, registers.sp, registers.delay_timer, registers.sound_timer
+    )];
+
+    for (row, values) in registers.v.chunks(4).enumerate() {
+        let line: Vec<String> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| format!("V{:X}: {:#04X}", row * 4 + i, value))
+            .collect();
+        lines.push(line.join("  "));
+    }
+
+    let stack: Vec<String> = registers
+        .stack
+        .iter()
+        .map(|addr| format!("{:#06X}", addr))
+        .collect();
+    if stack.is_empty() {
+        lines.push("stack:".to_string());
+    }
+    for (row, addrs) in stack.chunks(STACK_PER_LINE).enumerate() {
+        let label = if row == 0 { "stack:" } else { "      " };
+        lines.push(format!("{} {}", label, addrs.join(" ")));
+    }
+
+    let start = registers.pc.saturating_sub(CONTEXT * 2);
+    for addr in (start..=registers.pc.saturating_add(CONTEXT * 2)).step_by(2) {
+        let opcode = match cpu.memory().peek_opcode(addr) {
+            Ok(opcode) => opcode,
+            Err(_) => break,
+        };
+        let marker = match (addr == registers.pc, cpu.breakpoints().contains(&addr)) {
+            (true, _) => '>',
+            (false, true) => '*',
+            (false, false) => ' ',
+        };
+        let text = disasm::mnemonic(opcode, cpu.platform()).unwrap_or_else(|| "???".to_string());
+
+        lines.push(format!(
+            "{} {:#06X}: {:04X}  {}",
+            marker, addr, opcode, text
+        ));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,29 @@
+use super::*;
+use chip_8::{Platform, Quirks};
+
+#[test]
+fn test_panel() {
+    let mut cpu = Cpu::new(Platform::Chip8, Quirks::default());
+    // ten nested calls
+    let program: Vec<u8> = (0..10u16)
+        .flat_map(|i| (0x2202 + i * 2).to_be_bytes())
+        .collect();
+    cpu.load_program(&program).unwrap();
+    cpu.run(10).unwrap();
+    cpu.add_breakpoint(0x216);
+
+    let lines = panel(&cpu);
+
+    assert_eq!(
+        lines[0],
+        "PC: 0x0214  I: 0x0000  SP: 10  DT: 0x00  ST: 0x00"
+    );
+    assert_eq!(lines[1], "V0: 0x00  V1: 0x00  V2: 0x00  V3: 0x00");
+    assert!(lines[5].starts_with("stack: 0x0202 "));
+    assert_eq!(lines[6], "       0x0212 0x0214");
+    assert_eq!(lines[7], "  0x020C: 220E  CALL 0x20E");
+    assert_eq!(lines[11], "> 0x0214: 0000  SYS 0x000");
+    assert_eq!(lines[12], "* 0x0216: 0000  SYS 0x000");
+    // the overlay has room for 64 characters
+    assert!(lines.iter().all(|line| line.len() <= 64));
+}
@@ -0,0 +1,158 @@
+use sdl2::controller::Button;
+use sdl2::keyboard::Scancode;
+use std::collections::{BTreeMap, HashMap};
+
+// 1234/QWER/ASDF/ZXCV laid out like the keypad
+const KEYS: [(Scancode, usize); 16] = [
+    (Scancode::Num1, 0x1),
+    (Scancode::Num2, 0x2),
+    (Scancode::Num3, 0x3),
+    (Scancode::Num4, 0xC),
+    (Scancode::Q, 0x4),
+    (Scancode::W, 0x5),
+    (Scancode::E, 0x6),
+    (Scancode::R, 0xD),
+    (Scancode::A, 0x7),
+    (Scancode::S, 0x8),
+    (Scancode::D, 0x9),
+    (Scancode::F, 0xE),
+    (Scancode::Z, 0xA),
+    (Scancode::X, 0x0),
+    (Scancode::C, 0xB),
+    (Scancode::V, 0xF),
+];
+
+// the d-pad on W, A, S and D, which most games move with
+const BUTTONS: [(Button, usize); 6] = [
+    (Button::DPadUp, 0x5),
+    (Button::DPadLeft, 0x7),
+    (Button::DPadDown, 0x8),
+    (Button::DPadRight, 0x9),
+    (Button::A, 0x6),
+    (Button::B, 0x4),
+];
+
+/// A key of the keyboard or a button of a game controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    Key(Scancode),
+    Button(Button),
+}
+
+/// Which keypad key every bound input presses.
+pub struct Keymap {
+    bindings: HashMap<Input, usize>,
+    // inputs held down for every keypad key
+    held: [u8; 16],
+    // bound buttons held on every controller, by instance id
+    buttons_held: HashMap<u32, Vec<Button>>,
+}
+
+impl Keymap {
+    /// The default layout with `keys` and `buttons` replacing the bindings
+    /// of the keypad keys they mention.
+    pub fn new(
+        keys: &BTreeMap<usize, Vec<Scancode>>,
+        buttons: &BTreeMap<usize, Vec<Button>>,
+    ) -> Self {
+        let mut bindings: HashMap<Input, usize> = KEYS
+            .iter()
+            .map(|&(scancode, key)| (Input::Key(scancode), key))
+            .chain(
+                BUTTONS
+                    .iter()
+                    .map(|&(button, key)| (Input::Button(button), key)),
+            )
+            .collect();
+
+        for (&key, scancodes) in keys {
+            bindings.retain(|input, bound| !matches!(input, Input::Key(_)) || *bound != key);
+            bindings.extend(
+                scancodes
+                    .iter()
+                    .map(|&scancode| (Input::Key(scancode), key)),
+            );
+        }
+        for (&key, buttons) in buttons {
+            bindings.retain(|input, bound| !matches!(input, Input::Button(_)) || *bound != key);
+            bindings.extend(buttons.iter().map(|&button| (Input::Button(button), key)));
+        }
+
+        Self {
+            bindings,
+            held: [0; 16],
+            buttons_held: HashMap::new(),
+        }
+    }
+
+    pub fn is_bound(&self, input: Input) -> bool {
+        self.bindings.contains_key(&input)
+    }
+
+    /// The keypad key to press, inputs already held must not be passed again.
+    pub fn press(&mut self, input: Input) -> Option<usize> {
+        let key = *self.bindings.get(&input)?;
+        self.held[key] += 1;
+
+        Some(key)
+    }
+
+    /// The keypad key to release once no other input holds it down.
+    pub fn release(&mut self, input: Input) -> Option<usize> {
+        let key = *self.bindings.get(&input)?;
+        self.held[key] = self.held[key].saturating_sub(1);
+
+        (self.held[key] == 0).then_some(key)
+    }
+
+    /// Like `press`, remembering that `controller` holds the button.
+    pub fn press_button(&mut self, controller: u32, button: Button) -> Option<usize> {
+        let key = self.press(Input::Button(button))?;
+        self.buttons_held
+            .entry(controller)
+            .or_default()
+            .push(button);
+
+        Some(key)
+    }
+
+    /// Like `release`, for buttons pressed with `press_button`.
+    pub fn release_button(&mut self, controller: u32, button: Button) -> Option<usize> {
+        let held = self.buttons_held.get_mut(&controller)?;
+        let index = held.iter().position(|&held| held == button)?;
+        held.swap_remove(index);
+
+        self.release(Input::Button(button))
+    }
+
+    /// The keypad keys to release when `controller` is unplugged with
+    /// buttons still held.
+    pub fn remove_controller(&mut self, controller: u32) -> Vec<usize> {
+        let held = self.buttons_held.remove(&controller).unwrap_or_default();
+
+        held.into_iter()
+            .filter_map(|button| self.release(Input::Button(button)))
+            .collect()
+    }
+}
+
+/// `0` to `F`, as keys of the binding tables.
+pub fn keypad_key(text: &str) -> Result<usize, String> {
+    match usize::from_str_radix(text, 16) {
+        Ok(key) if key < 16 && text.len() == 1 => Ok(key),
+        _ => Err(format!("invalid keypad key: {}", text)),
+    }
+}
+
+// the names SDL uses, e.g. `W`, `Up` or `Keypad 8`, in any case
+pub fn scancode_from_name(name: &str) -> Result<Scancode, String> {
+    Scancode::from_name(name).ok_or_else(|| format!("unknown key: {}", name))
+}
+
+// the names of SDL's controller mappings, e.g. `a` or `dpup`
+pub fn button_from_name(name: &str) -> Result<Button, String> {
+    Button::from_string(name).ok_or_else(|| format!("unknown button: {}", name))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,72 @@
+use super::*;
+
+#[test]
+fn test_default_layout() {
+    let mut keymap = Keymap::new(&BTreeMap::new(), &BTreeMap::new());
+
+    assert_eq!(keymap.press(Input::Key(Scancode::Num4)), Some(0xC));
+    assert_eq!(keymap.press(Input::Key(Scancode::X)), Some(0x0));
+    assert_eq!(keymap.press(Input::Button(Button::DPadUp)), Some(0x5));
+    assert_eq!(keymap.press(Input::Key(Scancode::Up)), None);
+}
+
+#[test]
+fn test_bindings_replace_defaults() {
+    let keys = BTreeMap::from([(0x5, vec![Scancode::W, Scancode::Up]), (0x6, vec![])]);
+    let buttons = BTreeMap::from([(0x5, vec![Button::Y])]);
+    let keymap = Keymap::new(&keys, &buttons);
+
+    assert!(keymap.is_bound(Input::Key(Scancode::W)));
+    assert!(keymap.is_bound(Input::Key(Scancode::Up)));
+    assert!(!keymap.is_bound(Input::Key(Scancode::E)));
+    assert!(keymap.is_bound(Input::Button(Button::Y)));
+    assert!(!keymap.is_bound(Input::Button(Button::DPadUp)));
+    assert!(keymap.is_bound(Input::Button(Button::DPadDown)));
+}
+
+#[test]
+fn test_key_held_by_two_inputs() {
+    let keys = BTreeMap::from([(0x5, vec![Scancode::W, Scancode::Up])]);
+    let mut keymap = Keymap::new(&keys, &BTreeMap::new());
+
+    assert_eq!(keymap.press(Input::Key(Scancode::W)), Some(0x5));
+    assert_eq!(keymap.press(Input::Key(Scancode::Up)), Some(0x5));
+    assert_eq!(keymap.release(Input::Key(Scancode::W)), None);
+    assert_eq!(keymap.release(Input::Key(Scancode::Up)), Some(0x5));
+}
+
+#[test]
+fn test_unplugged_controller_releases_its_buttons() {
+    let mut keymap = Keymap::new(&BTreeMap::new(), &BTreeMap::new());
+
+    assert_eq!(keymap.press_button(0, Button::DPadUp), Some(0x5));
+    assert_eq!(keymap.press_button(0, Button::A), Some(0x6));
+    assert_eq!(keymap.press_button(1, Button::DPadUp), Some(0x5));
+    assert_eq!(keymap.press_button(1, Button::Start), None);
+
+    assert_eq!(keymap.remove_controller(0), vec![0x6]);
+    assert_eq!(keymap.release_button(0, Button::A), None);
+    assert_eq!(keymap.release_button(1, Button::DPadUp), Some(0x5));
+    assert!(keymap.remove_controller(1).is_empty());
+}
+
+#[test]
+fn test_names() {
+    assert_eq!(scancode_from_name("w"), Ok(Scancode::W));
+    assert_eq!(scancode_from_name("0"), Ok(Scancode::Num0));
+    assert_eq!(scancode_from_name("9"), Ok(Scancode::Num9));
+    assert_eq!(scancode_from_name("Up"), Ok(Scancode::Up));
+    assert_eq!(scancode_from_name("Keypad 8"), Ok(Scancode::Kp8));
+    assert_eq!(scancode_from_name("Keypad 0"), Ok(Scancode::Kp0));
+    assert_eq!(scancode_from_name("Left Shift"), Ok(Scancode::LShift));
+    assert_eq!(
+        scancode_from_name("Hyper"),
+        Err("unknown key: Hyper".to_string())
+    );
+
+    assert_eq!(button_from_name("dpleft"), Ok(Button::DPadLeft));
+    assert_eq!(button_from_name("z"), Err("unknown button: z".to_string()));
+
+    assert_eq!(keypad_key("c"), Ok(0xC));
+    assert!(keypad_key("10").is_err());
+}
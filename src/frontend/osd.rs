@@ -0,0 +1,308 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::time::{Duration, Instant};
+
+// the overlay is drawn at twice the resolution of the high resolution screen
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 128;
+const GLYPH_WIDTH: i32 = 3;
+const GLYPH_HEIGHT: i32 = 5;
+// one pixel of space after every glyph and around every line
+const ADVANCE: i32 = GLYPH_WIDTH + 1;
+const LINE_HEIGHT: i32 = GLYPH_HEIGHT + 2;
+const MESSAGE_TIME: Duration = Duration::from_secs(2);
+const ERROR_TIME: Duration = Duration::from_secs(4);
+// older messages give way to newer ones
+const MAX_MESSAGES: usize = 3;
+
+/// Text drawn over the game: short lived messages, an error banner, a
+/// frame and instruction counter and the debugger's panel.
+pub struct Osd {
+    messages: Vec<(String, Instant)>,
+    // without an end it stays until cleared
+    error: Option<(String, Option<Instant>)>,
+    counter: Option<Counter>,
+    // lines of the debugger while it is paused
+    panel: Vec<String>,
+    // drawn on the last frame, the screen has to be redrawn without it
+    shown: bool,
+}
+
+struct Counter {
+    since: Instant,
+    frames: u32,
+    instructions: u64,
+    text: String,
+}
+
+impl Osd {
+    pub fn new(counter: bool) -> Self {
+        let mut osd = Self {
+            messages: Vec::new(),
+            error: None,
+            counter: None,
+            panel: Vec::new(),
+            shown: false,
+        };
+        if counter {
+            osd.toggle_counter(0);
+        }
+
+        osd
+    }
+
+    pub fn show(&mut self, text: impl Into<String>) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+        self.messages
+            .push((text.into(), Instant::now() + MESSAGE_TIME));
+    }
+
+    // for a few seconds, unless a lasting error is shown
+    pub fn show_error(&mut self, text: impl Into<String>) {
+        if !matches!(self.error, Some((_, None))) {
+            self.error = Some((text.into(), Some(Instant::now() + ERROR_TIME)));
+        }
+    }
+
+    // until `clear_error`
+    pub fn set_error(&mut self, text: impl Into<String>) {
+        self.error = Some((text.into(), None));
+    }
+
+    pub fn clear_error(&mut self) {
+        self.error = None;
+    }
+
+    // replaces the panel, no lines hide it
+    pub fn set_panel(&mut self, lines: Vec<String>) {
+        self.panel = lines;
+    }
+
+    // returns whether the counter is shown now
+    pub fn toggle_counter(&mut self, instructions: u64) -> bool {
+        self.counter = match self.counter {
+            Some(_) => None,
+            None => Some(Counter {
+                since: Instant::now(),
+                frames: 0,
+                instructions,
+                text: String::new(),
+            }),
+        };
+
+        self.counter.is_some()
+    }
+
+    /// Called once per frame, drops old messages and updates the counter
+    /// every second.
+    pub fn update(&mut self, now: Instant, instructions: u64) {
+        self.messages.retain(|(_, until)| *until > now);
+        if matches!(self.error, Some((_, Some(until))) if until <= now) {
+            self.error = None;
+        }
+
+        if let Some(counter) = self.counter.as_mut() {
+            counter.frames += 1;
+            let elapsed = now.duration_since(counter.since).as_secs_f64();
+
+            if elapsed >= 1.0 {
+                let fps = counter.frames as f64 / elapsed;
+                let ips = (instructions - counter.instructions) as f64 / elapsed;
+                counter.text = format!("{:.0} FPS {:.0} IPS", fps, ips);
+                counter.since = now;
+                counter.frames = 0;
+                counter.instructions = instructions;
+            }
+        }
+    }
+
+    // the game has to be drawn again when the overlay changes
+    pub fn needs_redraw(&self) -> bool {
+        self.shown || self.is_visible()
+    }
+
+    fn is_visible(&self) -> bool {
+        !self.messages.is_empty()
+            || self.error.is_some()
+            || !self.panel.is_empty()
+            || self
+                .counter
+                .as_ref()
+                .is_some_and(|counter| !counter.text.is_empty())
+    }
+
+    /// Draws over what is on `canvas`, which must be set up for the game's
+    /// high resolution pixels and is left that way.
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>) {
+        self.shown = self.is_visible();
+        if !self.shown {
+            return;
+        }
+
+        let (width, height) = canvas.logical_size();
+        canvas.set_logical_size(WIDTH, HEIGHT).unwrap();
+        canvas.set_blend_mode(BlendMode::Blend);
+
+        // the error along the top, the panel below it, the counter in the
+        // top right corner under both and messages from the bottom up, newest
+        // last
+        let top = if self.error.is_some() { LINE_HEIGHT } else { 0 };
+        if let Some((text, _)) = &self.error {
+            draw_line(canvas, text, 0, Color::RGBA(160, 0, 0, 220));
+        }
+        for (index, text) in self.panel.iter().enumerate() {
+            let y = top + LINE_HEIGHT * index as i32;
+            draw_text(canvas, text, 0, y, Color::RGBA(0, 0, 0, 200));
+        }
+        if let Some(counter) = self
+            .counter
+            .as_ref()
+            .filter(|counter| !counter.text.is_empty())
+        {
+            let x = WIDTH as i32 - text_width(&counter.text) - 2;
+            let y = top + LINE_HEIGHT * self.panel.len() as i32;
+            draw_text(canvas, &counter.text, x, y, Color::RGBA(0, 0, 0, 160));
+        }
+        for (index, (text, _)) in self.messages.iter().rev().enumerate() {
+            let y = HEIGHT as i32 - LINE_HEIGHT * (index as i32 + 1);
+            draw_text(canvas, text, 0, y, Color::RGBA(0, 0, 0, 160));
+        }
+
+        canvas.set_blend_mode(BlendMode::None);
+        canvas.set_logical_size(width, height).unwrap();
+    }
+}
+
+// a line across the whole overlay
+fn draw_line(canvas: &mut Canvas<Window>, text: &str, y: i32, background: Color) {
+    canvas.set_draw_color(background);
+    canvas
+        .fill_rect(Rect::new(0, y, WIDTH, LINE_HEIGHT as u32))
+        .unwrap();
+    draw_glyphs(canvas, text, 1, y + 1);
+}
+
+// text on a box just big enough for it
+fn draw_text(canvas: &mut Canvas<Window>, text: &str, x: i32, y: i32, background: Color) {
+    let width = text_width(text) + 1;
+
+    canvas.set_draw_color(background);
+    canvas
+        .fill_rect(Rect::new(x, y, width as u32, LINE_HEIGHT as u32))
+        .unwrap();
+    draw_glyphs(canvas, text, x + 1, y + 1);
+}
+
+fn draw_glyphs(canvas: &mut Canvas<Window>, text: &str, x: i32, y: i32) {
+    let points: Vec<_> = text_pixels(text, x, y)
+        .map(|(x, y)| sdl2::rect::Point::new(x, y))
+        .collect();
+
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    canvas.draw_points(&points[..]).unwrap();
+}
+
+// text is cut off where it leaves the overlay
+fn text_width(text: &str) -> i32 {
+    (text.chars().count() as i32 * ADVANCE - 1).clamp(0, WIDTH as i32)
+}
+
+// the pixels of `text` with its top left corner at `x`, `y`
+fn text_pixels(text: &str, x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> + '_ {
+    text.chars().enumerate().flat_map(move |(index, c)| {
+        let left = x + index as i32 * ADVANCE;
+
+        glyph(c).iter().enumerate().flat_map(move |(row, bits)| {
+            (0..GLYPH_WIDTH)
+                .filter(move |column| bits & 0b100 >> column != 0)
+                .map(move |column| (left + column, y + row as i32))
+        })
+    })
+}
+
+// lowercase letters look like uppercase ones and anything else missing from
+// the font like `?`
+fn glyph(c: char) -> &'static [u8; 5] {
+    let c = c.to_ascii_uppercase();
+
+    match c {
+        ' '..='_' => &FONT[c as usize - ' ' as usize],
+        _ => &FONT['?' as usize - ' ' as usize],
+    }
+}
+
+// 3x5 glyphs from ' ' to '_', one row of three bits per byte
+#[rustfmt::skip]
+const FONT: [[u8; 5]; 64] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // ' '
+    [0b010, 0b010, 0b010, 0b000, 0b010], // !
+    [0b101, 0b101, 0b000, 0b000, 0b000], // "
+    [0b101, 0b111, 0b101, 0b111, 0b101], // #
+    [0b011, 0b110, 0b010, 0b011, 0b110], // $
+    [0b101, 0b001, 0b010, 0b100, 0b101], // %
+    [0b010, 0b101, 0b010, 0b101, 0b011], // &
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '
+    [0b001, 0b010, 0b010, 0b010, 0b001], // (
+    [0b100, 0b010, 0b010, 0b010, 0b100], // )
+    [0b000, 0b101, 0b010, 0b101, 0b000], // *
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ,
+    [0b000, 0b000, 0b111, 0b000, 0b000], // -
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b001, 0b001, 0b010, 0b100, 0b100], // /
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b010, 0b010], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ;
+    [0b001, 0b010, 0b100, 0b010, 0b001], // <
+    [0b000, 0b111, 0b000, 0b111, 0b000], // =
+    [0b100, 0b010, 0b001, 0b010, 0b100], // >
+    [0b111, 0b001, 0b011, 0b000, 0b010], // ?
+    [0b010, 0b101, 0b111, 0b100, 0b011], // @
+    [0b010, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b111, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b111, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b011, 0b010, 0b010, 0b010, 0b011], // [
+    [0b100, 0b100, 0b010, 0b001, 0b001], // \
+    [0b110, 0b010, 0b010, 0b010, 0b110], // ]
+    [0b010, 0b101, 0b000, 0b000, 0b000], // ^
+    [0b000, 0b000, 0b000, 0b000, 0b111], // _
+];
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,95 @@
+use super::*;
+
+#[test]
+fn test_glyphs() {
+    assert_eq!(glyph('a'), glyph('A'));
+    assert_eq!(glyph('~'), glyph('?'));
+    assert_eq!(glyph('0'), &[0b111, 0b101, 0b101, 0b101, 0b111]);
+}
+
+#[test]
+fn test_text_pixels() {
+    let pixels: Vec<_> = text_pixels("-.", 10, 20).collect();
+
+    assert_eq!(pixels, [(10, 22), (11, 22), (12, 22), (15, 24)]);
+    assert_eq!(text_width("-."), 7);
+    assert_eq!(text_width(""), 0);
+}
+
+#[test]
+fn test_messages_expire() {
+    let mut osd = Osd::new(false);
+
+    assert!(!osd.needs_redraw());
+
+    osd.show("saved state 1");
+    osd.show_error("can't save screenshot");
+    let start = Instant::now();
+    osd.update(start, 0);
+
+    assert!(osd.needs_redraw());
+
+    osd.update(start + MESSAGE_TIME, 0);
+
+    assert!(osd.messages.is_empty());
+    assert!(osd.error.is_some());
+
+    osd.update(start + ERROR_TIME, 0);
+
+    assert!(osd.error.is_none());
+    assert!(!osd.needs_redraw());
+}
+
+#[test]
+fn test_oldest_message_gives_way() {
+    let mut osd = Osd::new(false);
+
+    for speed in 10..15 {
+        osd.show(format!("speed {}", speed));
+    }
+
+    let texts: Vec<_> = osd.messages.iter().map(|(text, _)| text.as_str()).collect();
+    assert_eq!(texts, ["speed 12", "speed 13", "speed 14"]);
+}
+
+#[test]
+fn test_lasting_error() {
+    let mut osd = Osd::new(false);
+
+    osd.set_error("halted");
+    osd.show_error("can't save state 1");
+    osd.update(Instant::now() + ERROR_TIME, 0);
+
+    assert_eq!(osd.error, Some(("halted".to_string(), None)));
+
+    osd.clear_error();
+
+    assert!(osd.error.is_none());
+}
+
+#[test]
+fn test_counter() {
+    let mut osd = Osd::new(true);
+    let start = osd.counter.as_ref().unwrap().since;
+
+    for frame in 1..=60 {
+        osd.update(start + Duration::from_millis(frame * 1000 / 60), frame * 10);
+    }
+
+    assert_eq!(osd.counter.as_ref().unwrap().text, "60 FPS 600 IPS");
+    assert!(!osd.toggle_counter(600));
+    assert!(osd.counter.is_none());
+}
+
+#[test]
+fn test_panel() {
+    let mut osd = Osd::new(false);
+
+    osd.set_panel(vec!["PC: 0x0200".to_string()]);
+
+    assert!(osd.needs_redraw());
+
+    osd.set_panel(Vec::new());
+
+    assert!(!osd.needs_redraw());
+}
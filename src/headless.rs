@@ -0,0 +1,162 @@
+use crate::cpu::Cpu;
+use crate::error::CpuError;
+
+/// A key going down or up at the start of a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputEvent {
+    pub frame: u64,
+    pub key: usize,
+    pub pressed: bool,
+}
+
+/// Key presses and releases by frame, parsed from lines like `30 press A`.
+///
+/// Keys are hexadecimal digits and `#` starts a comment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputScript {
+    events: Vec<InputEvent>,
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let error = |message: &str| format!("line {}: {}", index + 1, message);
+
+            let (frame, action, key) = match words[..] {
+                [] => continue,
+                [frame, action, key] => (frame, action, key),
+                _ => return Err(error("expected <frame> press|release <key>")),
+            };
+
+            let frame = frame.parse().map_err(|_| error("invalid frame"))?;
+            let pressed = match action {
+                "press" => true,
+                "release" => false,
+                _ => return Err(error("expected press or release")),
+            };
+            let key = match usize::from_str_radix(key, 16) {
+                Ok(key) if key < 16 => key,
+                _ => return Err(error("invalid key")),
+            };
+
+            events.push(InputEvent {
+                frame,
+                key,
+                pressed,
+            });
+        }
+
+        // stable, so events of the same frame keep their order
+        events.sort_by_key(|event| event.frame);
+
+        Ok(Self { events })
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+}
+
+/// Runs a [`Cpu`] frame by frame without a window, as fast as it can.
+pub struct Runner {
+    pub ticks_per_frame: usize,
+    script: InputScript,
+    next_event: usize,
+    frame: u64,
+}
+
+impl Runner {
+    pub fn new(ticks_per_frame: usize, script: InputScript) -> Self {
+        Self {
+            ticks_per_frame,
+            script,
+            next_event: 0,
+            frame: 0,
+        }
+    }
+
+    // frames run so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn run_frame(&mut self, cpu: &mut Cpu) -> Result<(), CpuError> {
+        let events = self.script.events();
+
+        while let Some(event) = events.get(self.next_event) {
+            if event.frame > self.frame {
+                break;
+            }
+
+            if event.pressed {
+                cpu.key_press(event.key);
+            } else {
+                cpu.key_release(event.key);
+            }
+            self.next_event += 1;
+        }
+
+        cpu.run_frame(self.ticks_per_frame)?;
+        self.frame += 1;
+
+        Ok(())
+    }
+
+    // stops early when the rom exits with 00FD
+    pub fn run(&mut self, cpu: &mut Cpu, frames: u64) -> Result<(), CpuError> {
+        for _ in 0..frames {
+            if cpu.is_halted() {
+                break;
+            }
+
+            self.run_frame(cpu)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The registers, display size and memory of `cpu` as a JSON object, memory
+/// as one hexadecimal string.
+pub fn state_json(cpu: &Cpu, frames: u64) -> String {
+    let registers = cpu.registers();
+    let numbers = |values: &mut dyn Iterator<Item = String>| values.collect::<Vec<_>>().join(", ");
+    let memory = cpu.memory().peek(0, cpu.memory().size()).unwrap();
+    let memory: String = memory.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    format!(
+        r#"{{
+  "frames": {},
+  "halted": {},
+  "pc": {},
+  "i": {},
+  "sp": {},
+  "v": [{}],
+  "stack": [{}],
+  "delay_timer": {},
+  "sound_timer": {},
+  "display": {{ "width": {}, "height": {} }},
+  "memory": "{}"
+}}
+"#,
+        frames,
+        cpu.is_halted(),
+        registers.pc,
+        registers.i,
+        registers.sp,
+        numbers(&mut registers.v.iter().map(u8::to_string)),
+        numbers(&mut registers.stack.iter().map(u16::to_string)),
+        registers.delay_timer,
+        registers.sound_timer,
+        cpu.display().width(),
+        cpu.display().height(),
+        memory
+    )
+}
+
+#[cfg(test)]
+mod tests;
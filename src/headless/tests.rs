@@ -0,0 +1,86 @@
+use super::*;
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+
+fn set_up_cpu(program: &[u8]) -> Cpu {
+    let mut cpu = Cpu::with_seed(Platform::Chip8, Quirks::default(), 0);
+    cpu.load_program(program).unwrap();
+
+    cpu
+}
+
+#[test]
+fn test_parse_script() {
+    let script = InputScript::parse("# start\n20 release A\n\n10 press a # fire\n").unwrap();
+
+    assert_eq!(
+        script.events(),
+        &[
+            InputEvent {
+                frame: 10,
+                key: 0xA,
+                pressed: true
+            },
+            InputEvent {
+                frame: 20,
+                key: 0xA,
+                pressed: false
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_script_errors() {
+    assert_eq!(
+        InputScript::parse("1 press 5\n2 hold 5"),
+        Err("line 2: expected press or release".to_string())
+    );
+    assert_eq!(
+        InputScript::parse("1 press 10"),
+        Err("line 1: invalid key".to_string())
+    );
+    assert_eq!(
+        InputScript::parse("1 press"),
+        Err("line 1: expected <frame> press|release <key>".to_string())
+    );
+}
+
+#[test]
+fn test_runner_applies_input() {
+    // counts the frames key 5 is held in V0
+    let mut cpu = set_up_cpu(&[0x61, 0x05, 0xE1, 0xA1, 0x70, 0x01, 0x12, 0x02]);
+    let script = InputScript::parse("2 press 5\n4 release 5").unwrap();
+    let mut runner = Runner::new(3, script);
+
+    runner.run(&mut cpu, 6).unwrap();
+
+    assert_eq!(runner.frame(), 6);
+    assert!(cpu.registers().v[0] > 0);
+    assert!(!cpu.keypad()[5]);
+}
+
+#[test]
+fn test_runner_stops_on_exit() {
+    let mut cpu = Cpu::new(Platform::SuperChip, Quirks::SUPER_CHIP);
+    cpu.load_program(&[0x00, 0xFD]).unwrap();
+    let mut runner = Runner::new(10, InputScript::default());
+
+    runner.run(&mut cpu, 100).unwrap();
+
+    assert_eq!(runner.frame(), 1);
+}
+
+#[test]
+fn test_state_json() {
+    let mut cpu = set_up_cpu(&[0x6A, 0x2C]);
+    let mut runner = Runner::new(1, InputScript::default());
+    runner.run(&mut cpu, 1).unwrap();
+
+    let json = state_json(&cpu, runner.frame());
+
+    assert!(json.contains("\"frames\": 1,"));
+    assert!(json.contains("\"pc\": 514,"));
+    assert!(json.contains("\"v\": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 44, 0, 0, 0, 0, 0],"));
+    assert!(json.contains("\"display\": { \"width\": 64, \"height\": 32 },"));
+}
@@ -0,0 +1,27 @@
+//! CHIP-8, SUPER-CHIP and XO-CHIP emulator core.
+//!
+//! The core has no dependency on SDL, front ends drive a [`Cpu`] by calling
+//! [`Cpu::tick`] and [`Cpu::tick_timers`] and read back its [`Display`].
+
+pub mod asm;
+pub mod audio;
+pub mod cpu;
+pub mod disasm;
+pub mod display;
+pub mod error;
+pub mod font;
+pub mod headless;
+pub mod mem;
+pub mod movie;
+pub mod platform;
+pub mod quirks;
+pub mod record;
+pub mod rewind;
+pub mod rng;
+
+pub use cpu::{Break, Cpu, Registers, Timing, UnknownOpcodePolicy};
+pub use display::Display;
+pub use error::{CpuError, MovieError, StateError};
+pub use mem::Mem;
+pub use platform::Platform;
+pub use quirks::Quirks;
@@ -1,133 +1,600 @@
+use chip_8::display::Palette;
+use chip_8::headless::{InputScript, Runner};
+use chip_8::movie::Movie;
+use chip_8::{audio, cpu, rewind, Break};
+use frontend::beeper;
+use frontend::capture;
+use frontend::cli::{self, Cli};
+use frontend::config::Config;
+use frontend::debugger::{self, Command};
+use frontend::keymap::{Input, Keymap};
+use frontend::osd::Osd;
 use sdl2::event::Event;
-use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::pixels::Color;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-mod cpu;
-mod font;
-mod mem;
+mod frontend;
 
-const SCALE: u32 = 7;
 const WINDOW_NAME: &str = "CHIP-8 Emulator";
-const WINDOW_WIDTH: u32 = cpu::SCREEN_WIDTH as u32 * SCALE;
-const WINDOW_HEIGHT: u32 = cpu::SCREEN_HEIGHT as u32 * SCALE;
-const BG_COLOR: Color = Color::RGB(49, 57, 66);
-const FG_COLOR: Color = Color::RGB(216, 222, 233);
-const DELAY_TIME: Duration = Duration::new(0, 1_000_000_000 / 60);
+// unless the config file or the command line change them
+const TICKS_PER_FRAME: usize = 10;
+// size of a high resolution pixel, low resolution pixels are twice as big
+const SCALE: u32 = 4;
+const BEEP_WAVEFORM: audio::Waveform = audio::Waveform::Square;
+const BEEP_FREQUENCY: f32 = 440.0;
+const BEEP_VOLUME: f32 = 0.1;
+const FRAME_RATE: usize = 60;
+const DELAY_TIME: Duration = Duration::new(0, 1_000_000_000 / FRAME_RATE as u32);
+// seconds of gameplay that can be rewound
+const REWIND_SECONDS: usize = 10;
 
 fn main() {
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(Cli::Run(options)) => *options,
+        Ok(Cli::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(Cli::Version) => {
+            println!("chip-8 {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        // like the help, mistakes on the command line only go to the terminal
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(1);
+        }
+    };
+    // without a window errors can only go to the terminal
+    let headless = options.headless;
+
+    let rom = std::fs::read(&options.rom_path).unwrap_or_else(|err| {
+        let message = format!("can't read {}: {}", options.rom_path.display(), err);
+        fail(&message, headless)
+    });
+
+    // built in defaults, then the config file and its section for the rom,
+    // then the command line
+    let config = Config::load().unwrap_or_else(|err| fail(&err, headless));
+    let mut settings = config.settings(&rom);
+    settings.merge(options.settings.clone());
+
+    let platform = settings.platform(&options.rom_path);
+    let quirks = settings
+        .quirks(platform)
+        .unwrap_or_else(|err| fail(&err, headless));
+    let scale = settings.scale.unwrap_or(SCALE);
+
+    let mut cpu = match options.seed {
+        Some(seed) => cpu::Cpu::with_seed(platform, quirks, seed),
+        None => cpu::Cpu::new(platform, quirks),
+    };
+
+    cpu.set_unknown_opcode_policy(settings.unknown_opcodes.unwrap_or_default());
+    cpu.set_timing(settings.timing.unwrap_or_default());
+    cpu.load_program(&rom)
+        .unwrap_or_else(|err| fail(&err.to_string(), headless));
+    let mut ticks_per_frame = settings.ticks_per_frame.unwrap_or(TICKS_PER_FRAME);
+
+    if headless {
+        run_headless(&mut cpu, ticks_per_frame, options.frames);
+        return;
+    }
+
+    let program_path = options.rom_path.as_path();
+    let window_width = cpu::HIRES_SCREEN_WIDTH as u32 * scale;
+    let window_height = cpu::HIRES_SCREEN_HEIGHT as u32 * scale;
+
     let sdl_ctx = sdl2::init().unwrap();
     let video_subsytem = sdl_ctx.video().unwrap();
-    let window = video_subsytem
-        .window(WINDOW_NAME, WINDOW_WIDTH, WINDOW_HEIGHT)
-        .position_centered()
-        .build()
+    let mut window = video_subsytem.window(WINDOW_NAME, window_width, window_height);
+    window.position_centered();
+    if settings.fullscreen == Some(true) {
+        window.fullscreen_desktop();
+    }
+    let mut canvas = window.build().unwrap().into_canvas().build().unwrap();
+    // drawing happens in high resolution pixels, SDL scales them to the window
+    canvas
+        .set_logical_size(
+            cpu::HIRES_SCREEN_WIDTH as u32,
+            cpu::HIRES_SCREEN_HEIGHT as u32,
+        )
         .unwrap();
-    let mut canvas = window.into_canvas().build().unwrap();
     let mut event_pump = sdl_ctx.event_pump().unwrap();
-    let mut ticks_per_frame = 10;
+    let mut keymap = Keymap::new(&settings.keys, &settings.buttons);
+    // controllers are opened as SDL reports them, including those plugged in
+    // before starting
+    let controller_subsystem = sdl_ctx
+        .game_controller()
+        .map_err(|err| eprintln!("controllers disabled: {}", err))
+        .ok();
+    let mut controllers = Vec::new();
+    let mut crashed = false;
+    let mut rewinding = false;
+    // screenshots use the same colors as the window
+    let palette = settings.palette();
+    let mut recording: Option<(capture::Recorder, PathBuf)> = None;
 
-    canvas.set_draw_color(BG_COLOR);
+    canvas.set_draw_color(color(&palette, 0));
     canvas.clear();
     canvas.present();
 
-    let program_path = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| show_error_message("missing path to rom file", canvas.window()));
+    let mut debugger = debugger::Debugger::new(options.start_paused);
+    let mut osd = Osd::new(settings.counter == Some(true));
+    let mut movie = options
+        .movie_path
+        .clone()
+        .map(|path| (Movie::new(&cpu), path));
+
+    // a missing audio device shouldn't prevent playing
+    let tone = audio::Tone::new(
+        settings.waveform.unwrap_or(BEEP_WAVEFORM),
+        settings.frequency.unwrap_or(BEEP_FREQUENCY),
+        settings.volume.unwrap_or(BEEP_VOLUME),
+    );
+    let mut beeper = beeper::Beeper::new(&sdl_ctx, tone, platform.has_xo_opcodes())
+        .map_err(|err| eprintln!("audio disabled: {}", err))
+        .ok();
+    if let Some(beeper) = beeper.as_mut().filter(|_| settings.mute == Some(true)) {
+        beeper.toggle_mute();
+    }
+
+    let rewind_seconds = settings.rewind.unwrap_or(REWIND_SECONDS);
+    let mut rewind = rewind::Rewind::new(rewind_seconds * FRAME_RATE);
 
-    let mut cpu = cpu::Cpu::new();
-    cpu.load_rom(Path::new(&program_path))
-        .unwrap_or_else(|err| show_error_message(&err.to_string(), canvas.window()));
+    if debugger.is_paused() {
+        debugger::print_panel(&cpu);
+    }
 
     'emu: loop {
         let start = Instant::now();
+        let mut commands = debugger.poll();
+        let was_paused = debugger.is_paused();
 
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. } => break 'emu,
+                // bound keys go to the keypad before any shortcut
+                Event::KeyDown {
+                    scancode: Some(scancode),
+                    repeat: false,
+                    ..
+                } if keymap.is_bound(Input::Key(scancode)) => {
+                    cpu.key_press(keymap.press(Input::Key(scancode)).unwrap());
+                }
+                Event::KeyDown {
+                    scancode: Some(scancode),
+                    ..
+                } if keymap.is_bound(Input::Key(scancode)) => (),
+                Event::KeyUp {
+                    scancode: Some(scancode),
+                    ..
+                } if keymap.is_bound(Input::Key(scancode)) => {
+                    if let Some(key) = keymap.release(Input::Key(scancode)) {
+                        cpu.key_release(key);
+                    }
+                }
+                Event::ControllerButtonDown { which, button, .. } => {
+                    if let Some(key) = keymap.press_button(which, button) {
+                        cpu.key_press(key);
+                    }
+                }
+                Event::ControllerButtonUp { which, button, .. } => {
+                    if let Some(key) = keymap.release_button(which, button) {
+                        cpu.key_release(key);
+                    }
+                }
+                Event::ControllerDeviceAdded { which, .. } => {
+                    if let Some(subsystem) = controller_subsystem.as_ref() {
+                        match subsystem.open(which) {
+                            Ok(controller) => {
+                                println!("connected {}", controller.name());
+                                controllers.push(controller);
+                            }
+                            Err(err) => eprintln!("can't open controller: {}", err),
+                        }
+                    }
+                }
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    controllers.retain(|controller| controller.instance_id() != which);
+                    // no button up events come for buttons held while unplugged
+                    for key in keymap.remove_controller(which) {
+                        cpu.key_release(key);
+                    }
+                }
                 Event::KeyDown {
-                    keycode: Some(key), ..
-                } if key == Keycode::Up => {
+                    keycode: Some(Keycode::Up),
+                    ..
+                } => {
                     if ticks_per_frame < 25 {
                         ticks_per_frame += 1
                     }
+                    osd.show(speed_message(cpu.timing(), ticks_per_frame));
                 }
                 Event::KeyDown {
-                    keycode: Some(key), ..
-                } if key == Keycode::Down => {
+                    keycode: Some(Keycode::Down),
+                    ..
+                } => {
                     if ticks_per_frame > 8 {
                         ticks_per_frame -= 1
                     }
+                    osd.show(speed_message(cpu.timing(), ticks_per_frame));
                 }
-                Event::KeyDown { scancode, .. } => {
-                    if let Some(key) = get_key(scancode) {
-                        cpu.key_press(key);
+                Event::KeyDown {
+                    keycode: Some(Keycode::M),
+                    ..
+                } => match beeper.as_mut().map(beeper::Beeper::toggle_mute) {
+                    Some(true) => osd.show("muted"),
+                    Some(false) => osd.show("sound on"),
+                    None => osd.show_error("no audio device"),
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                } => rewinding = true,
+                Event::KeyUp {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                } => rewinding = false,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F5),
+                    ..
+                } if debugger.is_paused() => commands.push(Command::Continue),
+                Event::KeyDown {
+                    keycode: Some(Keycode::F5),
+                    ..
+                } => commands.push(Command::Pause),
+                Event::KeyDown {
+                    keycode: Some(Keycode::F6),
+                    ..
+                } => commands.push(Command::Step),
+                Event::KeyDown {
+                    keycode: Some(Keycode::F7),
+                    ..
+                } => commands.push(Command::StepFrame),
+                Event::KeyDown {
+                    keycode: Some(Keycode::F10),
+                    ..
+                } => {
+                    osd.toggle_counter(cpu.instructions());
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F11),
+                    ..
+                } => match recording.take() {
+                    Some((recorder, path)) => {
+                        stop_recording(recorder, &path);
+                        osd.show("recording stopped");
+                    }
+                    None => match capture::start_recording(&palette, program_path) {
+                        Ok((recorder, path)) => {
+                            println!("recording to {}", path.display());
+                            osd.show("recording");
+                            recording = Some((recorder, path));
+                        }
+                        Err(err) => {
+                            eprintln!("can't start recording: {}", err);
+                            osd.show_error(format!("can't start recording: {}", err));
+                        }
+                    },
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::F12),
+                    keymod,
+                    ..
+                } => {
+                    // shift saves it as big as the window
+                    let scale = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        scale as usize * cpu::HIRES_SCREEN_WIDTH / cpu.display().width()
+                    } else {
+                        1
+                    };
+
+                    match capture::screenshot(cpu.display(), &palette, scale, program_path) {
+                        Ok(path) => {
+                            println!("saved screenshot to {}", path.display());
+                            osd.show("saved screenshot");
+                        }
+                        Err(err) => {
+                            eprintln!("can't save screenshot: {}", err);
+                            osd.show_error(format!("can't save screenshot: {}", err));
+                        }
                     }
                 }
-                Event::KeyUp { scancode, .. } => {
-                    if let Some(key) = get_key(scancode) {
-                        cpu.key_release(key);
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    keymod,
+                    ..
+                } if state_slot(keycode).is_some() => {
+                    let slot = state_slot(keycode).unwrap();
+                    let path = format!("{}.state{}", program_path.display(), slot);
+
+                    if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        match cpu.load_state_from_file(Path::new(&path)) {
+                            Ok(()) => {
+                                println!("loaded state {}", slot);
+                                osd.show(format!("loaded state {}", slot));
+                                stop_movie(&mut movie, "a state was loaded");
+                                crashed = false;
+                                osd.clear_error();
+                                rewind.clear();
+                                canvas.window_mut().set_title(WINDOW_NAME).unwrap();
+                            }
+                            Err(err) => {
+                                eprintln!("can't load state {}: {}", slot, err);
+                                osd.show_error(format!("can't load state {}: {}", slot, err));
+                            }
+                        }
+                    } else {
+                        match cpu.save_state_to_file(Path::new(&path)) {
+                            Ok(()) => {
+                                println!("saved state {}", slot);
+                                osd.show(format!("saved state {}", slot));
+                            }
+                            Err(err) => {
+                                eprintln!("can't save state {}: {}", slot, err);
+                                osd.show_error(format!("can't save state {}: {}", slot, err));
+                            }
+                        }
                     }
                 }
                 _ => (),
             }
         }
 
-        // TODO: Add feedback when ticks_per_frame is changed
+        // the panel follows every step while paused
+        if debugger.is_paused() {
+            osd.set_panel(debugger::panel(&cpu));
+        } else {
+            osd.set_panel(Vec::new());
+        }
+        osd.update(start, cpu.instructions());
+
+        if cpu.draw_flag || osd.needs_redraw() {
+            let display = cpu.display();
+            let pixel_size = (cpu::HIRES_SCREEN_WIDTH / display.width()) as u32;
 
-        if cpu.draw_flag {
-            for (i, pixel) in cpu.display().iter().enumerate() {
-                let color = if *pixel { FG_COLOR } else { BG_COLOR };
-                let x = (i % cpu::SCREEN_WIDTH) as i32 * SCALE as i32;
-                let y = (i / cpu::SCREEN_WIDTH) as i32 * SCALE as i32;
-                let rect = sdl2::rect::Rect::new(x, y, SCALE, SCALE);
+            for (i, pixel) in display.iter().enumerate() {
+                let color = color(&palette, *pixel);
+                let x = (i % display.width()) as u32 * pixel_size;
+                let y = (i / display.width()) as u32 * pixel_size;
+                let rect = sdl2::rect::Rect::new(x as i32, y as i32, pixel_size, pixel_size);
 
                 canvas.set_draw_color(color);
                 canvas.fill_rect(rect).unwrap();
             }
 
+            osd.draw(&mut canvas);
             canvas.present();
             cpu.draw_flag = false;
         }
 
-        for _ in 0..ticks_per_frame {
-            cpu.tick();
+        // every frame is recorded, also when the screen didn't change
+        if let Some((recorder, path)) = recording.as_mut() {
+            if let Err(err) = recorder.record_frame(cpu.display()) {
+                eprintln!("can't record to {}: {}", path.display(), err);
+                osd.show_error(format!("can't record: {}", err));
+                recording = None;
+            }
+        }
+
+        // holding backspace steps back one frame per frame
+        if rewinding {
+            if let Some(state) = rewind.pop() {
+                cpu.load_state(&state).unwrap();
+                stop_movie(&mut movie, "the machine was rewound");
+
+                if crashed {
+                    crashed = false;
+                    osd.clear_error();
+                    canvas.window_mut().set_title(WINDOW_NAME).unwrap();
+                }
+            }
+        }
+
+        let mut steps = 0;
+        let mut step_frame = false;
+
+        for command in commands {
+            match command {
+                Command::Continue => debugger.resume(),
+                Command::Pause => debugger.pause(&cpu),
+                // stepping a running machine only pauses it
+                Command::Step | Command::StepFrame if !debugger.is_paused() => debugger.pause(&cpu),
+                Command::Step => steps += 1,
+                Command::StepFrame => step_frame = true,
+                Command::Break(addr) => {
+                    cpu.add_breakpoint(addr);
+                    println!("breakpoint at {:#06X}", addr);
+                }
+                Command::Delete(addr) => {
+                    if cpu.remove_breakpoint(addr) {
+                        println!("deleted breakpoint at {:#06X}", addr);
+                    }
+                }
+                Command::Watch(watchpoint) => {
+                    cpu.memory_mut().add_watchpoint(watchpoint);
+                    println!("watching {:#06X}-{:#06X}", watchpoint.start, watchpoint.end);
+                }
+                Command::Unwatch(addr) => {
+                    if cpu.memory_mut().remove_watchpoint(addr) {
+                        println!("deleted watchpoint at {:#06X}", addr);
+                    }
+                }
+                Command::Trace => {
+                    let tracing = debugger::toggle_trace(&mut cpu);
+                    println!("trace {}", if tracing { "on" } else { "off" });
+                }
+                Command::Show => debugger::print_panel(&cpu),
+            }
+        }
+
+        let run_frame = !debugger.is_paused() || step_frame;
+
+        // a faulty rom halts the machine but keeps the window open
+        if !crashed && !rewinding {
+            let mut result = Ok(None);
+            let instructions = cpu.instructions();
+
+            for _ in 0..steps {
+                result = cpu.step().map(|_| None);
+                if result.is_err() {
+                    break;
+                }
+            }
+            if run_frame && result.is_ok() {
+                result = cpu.run_frame(ticks_per_frame);
+            }
+
+            // movies only hold whole frames, a crash ends them anyway
+            if steps > 0 || matches!(result, Ok(Some(_))) {
+                stop_movie(&mut movie, "the debugger interrupted a frame");
+            } else if let Some((movie, _)) = movie.as_mut().filter(|_| run_frame) {
+                // fewer than ticks_per_frame when the display wait ended it
+                let ticks = (cpu.instructions() - instructions) as usize;
+                movie.record_frame(&mut cpu, ticks);
+            }
+
+            match result {
+                Err(err) => {
+                    crashed = true;
+
+                    let title = format!("{} - halted", WINDOW_NAME);
+                    canvas.window_mut().set_title(&title).unwrap();
+                    eprintln!("{}", err.diagnostic(platform));
+                    osd.set_error(format!("halted: {}", err));
+                }
+                Ok(Some(Break::Breakpoint(addr))) => {
+                    println!("breakpoint hit at {:#06X}", addr);
+                    debugger.pause(&cpu);
+                }
+                Ok(Some(Break::Watchpoint(access))) => {
+                    println!("watchpoint hit: {}", debugger::format_access(&access));
+                    debugger.pause(&cpu);
+                }
+                Ok(None) if steps > 0 || step_frame => debugger::print_panel(&cpu),
+                Ok(None) => (),
+            }
+        }
+
+        for access in cpu.memory_mut().take_trace() {
+            println!("{}", debugger::format_access(&access));
+        }
+
+        for err in cpu.take_unknown_opcodes() {
+            eprintln!("{}", err.diagnostic(platform));
+        }
+
+        if !crashed && !rewinding && run_frame {
+            rewind.push(cpu.save_state());
         }
 
-        cpu.tick_timers();
+        // by a key, a command or a breakpoint
+        match (was_paused, debugger.is_paused()) {
+            (false, true) => osd.show("paused"),
+            (true, false) => osd.show("resumed"),
+            _ => (),
+        }
+
+        if let Some(beeper) = beeper.as_mut() {
+            if crashed || rewinding || debugger.is_paused() {
+                beeper.silence();
+            } else {
+                beeper.update(&cpu);
+            }
+        }
+
+        // 00FD exits the interpreter
+        if cpu.is_halted() {
+            break 'emu;
+        }
 
         if let Some(delay) = DELAY_TIME.checked_sub(start.elapsed()) {
             std::thread::sleep(delay);
         }
     }
+
+    if let Some((recorder, path)) = recording {
+        stop_recording(recorder, &path);
+    }
+    stop_movie(&mut movie, "the emulator was closed");
+}
+
+// runs as fast as possible until the rom exits, crashes or `--frames` passed
+fn run_headless(cpu: &mut cpu::Cpu, ticks_per_frame: usize, frames: Option<u64>) {
+    let mut runner = Runner::new(ticks_per_frame, InputScript::default());
+    let platform = cpu.platform();
+
+    while !cpu.is_halted() && frames.is_none_or(|frames| runner.frame() < frames) {
+        let result = runner.run_frame(cpu);
+
+        for err in cpu.take_unknown_opcodes() {
+            eprintln!("{}", err.diagnostic(platform));
+        }
+        if let Err(err) = result {
+            fail(&err.diagnostic(platform), true);
+        }
+    }
 }
 
-fn get_key(scancode: Option<Scancode>) -> Option<usize> {
-    match scancode {
-        Some(Scancode::Num1) => Some(0x1),
-        Some(Scancode::Num2) => Some(0x2),
-        Some(Scancode::Num3) => Some(0x3),
-        Some(Scancode::Num4) => Some(0xC),
-        Some(Scancode::Q) => Some(0x4),
-        Some(Scancode::W) => Some(0x5),
-        Some(Scancode::E) => Some(0x6),
-        Some(Scancode::R) => Some(0xD),
-        Some(Scancode::A) => Some(0x7),
-        Some(Scancode::S) => Some(0x8),
-        Some(Scancode::D) => Some(0x9),
-        Some(Scancode::F) => Some(0xE),
-        Some(Scancode::Z) => Some(0xA),
-        Some(Scancode::X) => Some(0x0),
-        Some(Scancode::C) => Some(0xB),
-        Some(Scancode::V) => Some(0xF),
+fn stop_movie(movie: &mut Option<(Movie, PathBuf)>, reason: &str) {
+    if let Some((movie, path)) = movie.take() {
+        match movie.save(&path) {
+            Ok(()) => println!(
+                "saved {} frames of input to {}, recording stopped because {}",
+                movie.frames.len(),
+                path.display(),
+                reason
+            ),
+            Err(err) => eprintln!("can't save input to {}: {}", path.display(), err),
+        }
+    }
+}
+
+fn stop_recording(recorder: capture::Recorder, path: &Path) {
+    let frames = recorder.frames();
+
+    match recorder.finish() {
+        Ok(_) => println!("recorded {} frames to {}", frames, path.display()),
+        Err(err) => eprintln!("can't record to {}: {}", path.display(), err),
+    }
+}
+
+// F1-F4 save to a slot, with shift they load from it
+fn state_slot(keycode: Keycode) -> Option<u8> {
+    match keycode {
+        Keycode::F1 => Some(1),
+        Keycode::F2 => Some(2),
+        Keycode::F3 => Some(3),
+        Keycode::F4 => Some(4),
         _ => None,
     }
 }
 
-fn show_error_message(message: &str, window: &sdl2::video::Window) -> ! {
+// shown when Up or Down change the speed
+fn speed_message(timing: cpu::Timing, ticks_per_frame: usize) -> String {
+    match timing {
+        cpu::Timing::Instructions => format!("speed: {} instructions per frame", ticks_per_frame),
+        cpu::Timing::CosmacVip => "speed: set by the vip timing".to_string(),
+    }
+}
+
+fn color(palette: &Palette, pixel: u8) -> Color {
+    let [r, g, b] = palette.0[pixel as usize];
+
+    Color::RGB(r, g, b)
+}
+
+// also shows a message box when the emulator was started with a window, the
+// terminal may not be visible
+fn fail(message: &str, headless: bool) -> ! {
     use sdl2::messagebox::{show_simple_message_box, MessageBoxFlag};
 
-    show_simple_message_box(MessageBoxFlag::ERROR, "Error", message, window).unwrap();
+    eprintln!("{}", message);
+    if !headless {
+        let _ = show_simple_message_box(MessageBoxFlag::ERROR, "Error", message, None);
+    }
     std::process::exit(1);
 }
@@ -1,47 +1,281 @@
 use crate::cpu::START_ADDRESS;
-use crate::font::{FONT_ADDRESS, FONT_SPRITE};
+use crate::font::{BIG_FONT_ADDRESS, BIG_FONT_SPRITE, FONT_ADDRESS, FONT_SPRITE};
+use std::collections::VecDeque;
 
 pub const MEM_SIZE: usize = 4096;
+pub const XO_MEM_SIZE: usize = 65536;
+
+// first address past the end of memory touched by an access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub addr: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessKind {
+    Read,
+    Write,
+    // fetching an instruction
+    Execute,
+}
+
+/// A byte accessed by the instruction at `pc`, with the value read or
+/// written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Access {
+    pub pc: u16,
+    pub opcode: u16,
+    pub addr: u16,
+    pub value: u8,
+    pub kind: AccessKind,
+}
+
+/// Watches the addresses from `start` to `end` inclusive for the selected
+/// kinds of access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Watchpoint {
+    fn matches(&self, addr: u16, kind: AccessKind) -> bool {
+        let watched = match kind {
+            AccessKind::Read => self.read,
+            AccessKind::Write => self.write,
+            AccessKind::Execute => self.execute,
+        };
+
+        watched && (self.start..=self.end).contains(&addr)
+    }
+}
+
+// only allocated while there are watchpoints or a trace, so plain accesses
+// cost a single check
+#[derive(Default)]
+struct Monitor {
+    pc: u16,
+    opcode: u16,
+    watchpoints: Vec<Watchpoint>,
+    trace: Option<VecDeque<Access>>,
+    // 0 for no limit
+    trace_limit: usize,
+    hit: Option<Access>,
+}
+
+impl Monitor {
+    fn record(&mut self, addr: u16, bytes: &[u8], kind: AccessKind) {
+        for (offset, value) in bytes.iter().enumerate() {
+            let access = Access {
+                pc: self.pc,
+                opcode: self.opcode,
+                addr: addr.wrapping_add(offset as u16),
+                value: *value,
+                kind,
+            };
+
+            if self.hit.is_none()
+                && self
+                    .watchpoints
+                    .iter()
+                    .any(|w| w.matches(access.addr, kind))
+            {
+                self.hit = Some(access);
+            }
+
+            if let Some(trace) = self.trace.as_mut() {
+                if self.trace_limit != 0 && trace.len() == self.trace_limit {
+                    trace.pop_front();
+                }
+                trace.push_back(access);
+            }
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.watchpoints.is_empty() && self.trace.is_none()
+    }
+}
 
 pub struct Mem {
-    memory: [u8; MEM_SIZE],
+    memory: Vec<u8>,
+    monitor: Option<Box<Monitor>>,
 }
 
 impl Mem {
-    pub fn new() -> Self {
+    pub fn new(size: usize) -> Self {
         Self {
-            memory: [0; MEM_SIZE],
+            memory: vec![0; size],
+            monitor: None,
         }
     }
 
+    pub fn size(&self) -> usize {
+        self.memory.len()
+    }
+
     pub fn load_font(&mut self) {
-        self.write(FONT_ADDRESS, &FONT_SPRITE);
+        // both fonts fit in the smallest memory
+        let font = FONT_ADDRESS as usize..FONT_ADDRESS as usize + FONT_SPRITE.len();
+        let big_font = BIG_FONT_ADDRESS as usize..BIG_FONT_ADDRESS as usize + BIG_FONT_SPRITE.len();
+
+        self.memory[font].copy_from_slice(&FONT_SPRITE);
+        self.memory[big_font].copy_from_slice(&BIG_FONT_SPRITE);
+    }
+
+    // replaces the whole memory without going through watchpoints or traces,
+    // the size stays the same
+    pub(crate) fn restore(&mut self, memory: &[u8]) {
+        self.memory.copy_from_slice(memory);
     }
 
-    pub fn load_program(&mut self, program: &[u8]) {
-        self.write(START_ADDRESS, &program);
+    pub fn load_program(&mut self, program: &[u8]) -> Result<(), OutOfBounds> {
+        self.write(START_ADDRESS, program)
     }
 
-    pub fn read(&self, addr: u16, length: usize) -> &[u8] {
+    pub fn read(&mut self, addr: u16, length: usize) -> Result<&[u8], OutOfBounds> {
         let start = addr as usize;
         let end = start + length;
+        let bytes = self
+            .memory
+            .get(start..end)
+            .ok_or(self.out_of_bounds(start))?;
+
+        if let Some(monitor) = self.monitor.as_mut() {
+            monitor.record(addr, bytes, AccessKind::Read);
+        }
 
-        &self.memory[start..end]
+        Ok(bytes)
     }
 
-    pub fn write(&mut self, addr: u16, value: &[u8]) {
+    pub fn write(&mut self, addr: u16, value: &[u8]) -> Result<(), OutOfBounds> {
         let start = addr as usize;
         let end = start + value.len();
+        let error = self.out_of_bounds(start);
 
-        self.memory[start..end].copy_from_slice(value);
+        self.memory
+            .get_mut(start..end)
+            .ok_or(error)?
+            .copy_from_slice(value);
+
+        if let Some(monitor) = self.monitor.as_mut() {
+            monitor.record(addr, value, AccessKind::Write);
+        }
+
+        Ok(())
     }
 
-    pub fn read_opcode(&self, addr: u16) -> u16 {
+    pub fn read_opcode(&mut self, addr: u16) -> Result<u16, OutOfBounds> {
         let mut word = [0u8; 2];
-        let addr = addr as usize;
 
-        word.copy_from_slice(&self.memory[addr..addr + 2]);
+        word.copy_from_slice(self.read(addr, 2)?);
+
+        Ok(u16::from_be_bytes(word))
+    }
 
-        u16::from_be_bytes(word)
+    // reads the instruction at pc, which every following access is traced to
+    pub fn fetch(&mut self, pc: u16) -> Result<u16, OutOfBounds> {
+        let opcode = self.peek_opcode(pc)?;
+
+        if let Some(monitor) = self.monitor.as_mut() {
+            monitor.pc = pc;
+            monitor.opcode = opcode;
+            monitor.record(pc, &opcode.to_be_bytes(), AccessKind::Execute);
+        }
+
+        Ok(opcode)
+    }
+
+    // like read but invisible to watchpoints and traces
+    pub fn peek(&self, addr: u16, length: usize) -> Result<&[u8], OutOfBounds> {
+        let start = addr as usize;
+        let end = start + length;
+
+        self.memory.get(start..end).ok_or(self.out_of_bounds(start))
+    }
+
+    pub fn peek_opcode(&self, addr: u16) -> Result<u16, OutOfBounds> {
+        let mut word = [0u8; 2];
+
+        word.copy_from_slice(self.peek(addr, 2)?);
+
+        Ok(u16::from_be_bytes(word))
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        self.monitor().watchpoints.push(watchpoint);
+    }
+
+    // removes every watchpoint that starts at addr, returns false when none did
+    pub fn remove_watchpoint(&mut self, start: u16) -> bool {
+        let monitor = self.monitor();
+        let count = monitor.watchpoints.len();
+        monitor.watchpoints.retain(|w| w.start != start);
+        let removed = monitor.watchpoints.len() != count;
+        self.drop_idle_monitor();
+
+        removed
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        match &self.monitor {
+            Some(monitor) => &monitor.watchpoints,
+            None => &[],
+        }
+    }
+
+    // keeps the last `limit` accesses until taken, or every access with a
+    // limit of 0
+    pub fn start_trace(&mut self, limit: usize) {
+        let monitor = self.monitor();
+        monitor.trace = Some(VecDeque::new());
+        monitor.trace_limit = limit;
+    }
+
+    pub fn stop_trace(&mut self) {
+        if let Some(monitor) = self.monitor.as_mut() {
+            monitor.trace = None;
+        }
+        self.drop_idle_monitor();
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        matches!(&self.monitor, Some(monitor) if monitor.trace.is_some())
+    }
+
+    // the accesses traced since the last call, oldest first
+    pub fn take_trace(&mut self) -> Vec<Access> {
+        match self
+            .monitor
+            .as_mut()
+            .and_then(|monitor| monitor.trace.as_mut())
+        {
+            Some(trace) => trace.drain(..).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // first watched access since the last call
+    pub(crate) fn take_hit(&mut self) -> Option<Access> {
+        self.monitor.as_mut()?.hit.take()
+    }
+
+    fn monitor(&mut self) -> &mut Monitor {
+        self.monitor.get_or_insert_with(Box::default)
+    }
+
+    fn drop_idle_monitor(&mut self) {
+        if matches!(&self.monitor, Some(monitor) if monitor.is_idle()) {
+            self.monitor = None;
+        }
+    }
+
+    fn out_of_bounds(&self, start: usize) -> OutOfBounds {
+        OutOfBounds {
+            addr: start.max(self.memory.len()),
+        }
     }
 }
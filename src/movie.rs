@@ -0,0 +1,306 @@
+// Input movies are text files, a header naming the rom by its SHA-1 with
+// the platform, quirks and rng seed it ran with, then one line per frame:
+//
+//     chip8-movie 1
+//     rom 4e9c0b0b4fbd5e7c0e1e76e0c9e9c0e0f4b6d0a2
+//     platform chip8
+//     quirks shift_uses_vy=0 memory_increment=unchanged ...
+//     seed 1234
+//     rng splitmix64
+//     frames
+//     0020 0001 10 5f1e4c3b2a190807
+//
+// a frame line is the keypad as a bitmask, the keys released before the
+// frame ran, the instructions run and a hash of the screen afterwards. A key
+// tapped between two frames is only in the releases, which FX0A waits for
+// on some platforms. The seed is enough to get the same CXNN values again, so
+// they aren't stored, but the generator is named and movies for another one
+// are rejected instead of going out of sync.
+
+use crate::cpu::Cpu;
+use crate::display::Display;
+use crate::error::MovieError;
+use crate::platform::Platform;
+use crate::quirks::Quirks;
+use crate::rng;
+use std::fmt::Write;
+use std::path::Path;
+
+const HEADER: &str = "chip8-movie 1";
+
+/// One frame of a movie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MovieFrame {
+    // bit N is set while key N is down
+    pub keypad: u16,
+    // bit N is set when key N was released since the last frame
+    pub released: u16,
+    pub ticks: usize,
+    pub display_hash: u64,
+}
+
+/// Everything needed to run a rom again exactly as it was played.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Movie {
+    pub rom_hash: [u8; 20],
+    pub platform: Platform,
+    pub quirks: Quirks,
+    pub seed: u64,
+    pub frames: Vec<MovieFrame>,
+}
+
+impl Movie {
+    /// Starts a movie of `cpu`, which must have just loaded its rom.
+    pub fn new(cpu: &Cpu) -> Self {
+        Self {
+            rom_hash: cpu.rom_hash(),
+            platform: cpu.platform(),
+            quirks: cpu.quirks(),
+            seed: cpu.seed(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Adds a frame after `cpu` ran `ticks` instructions and its timers,
+    /// taking the key releases since the last frame.
+    pub fn record_frame(&mut self, cpu: &mut Cpu, ticks: usize) {
+        self.frames.push(MovieFrame {
+            keypad: keypad_bits(cpu.keypad()),
+            released: cpu.take_key_releases(),
+            ticks,
+            display_hash: display_hash(cpu.display()),
+        });
+    }
+
+    /// A machine set up the way the movie was recorded, the rom still has
+    /// to be loaded.
+    pub fn cpu(&self) -> Cpu {
+        Cpu::with_seed(self.platform, self.quirks, self.seed)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let hash: String = self.rom_hash.iter().map(|b| format!("{:02x}", b)).collect();
+
+        writeln!(out, "{}", HEADER).unwrap();
+        writeln!(out, "rom {}", hash).unwrap();
+        writeln!(out, "platform {}", self.platform.name()).unwrap();
+        writeln!(out, "quirks {}", self.quirks).unwrap();
+        writeln!(out, "seed {}", self.seed).unwrap();
+        writeln!(out, "rng {}", rng::ALGORITHM).unwrap();
+        writeln!(out, "frames").unwrap();
+
+        for frame in &self.frames {
+            writeln!(
+                out,
+                "{:04x} {:04x} {} {:016x}",
+                frame.keypad, frame.released, frame.ticks, frame.display_hash
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    pub fn parse(text: &str) -> Result<Self, MovieError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(parse_error(1, "not an input movie"));
+        }
+        let (line, rom) = field(&mut lines, "rom")?;
+        let rom_hash = parse_hash(rom).ok_or_else(|| parse_error(line, "invalid rom hash"))?;
+        let (line, platform) = field(&mut lines, "platform")?;
+        let platform =
+            Platform::from_name(platform).ok_or_else(|| parse_error(line, "unknown platform"))?;
+        let (line, quirks) = field(&mut lines, "quirks")?;
+        let quirks = quirks_from_text(quirks).map_err(|message| parse_error(line, &message))?;
+        let (line, seed) = field(&mut lines, "seed")?;
+        let seed = seed
+            .parse()
+            .map_err(|_| parse_error(line, "invalid seed"))?;
+        let (_, algorithm) = field(&mut lines, "rng")?;
+        if algorithm != rng::ALGORITHM {
+            return Err(MovieError::UnsupportedRng(algorithm.to_string()));
+        }
+        field(&mut lines, "frames")?;
+
+        let mut frames = Vec::new();
+        for (line, text) in lines {
+            frames.push(parse_frame(text).ok_or_else(|| parse_error(line, "invalid frame"))?);
+        }
+
+        Ok(Self {
+            rom_hash,
+            platform,
+            quirks,
+            seed,
+            frames,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), MovieError> {
+        std::fs::write(path, self.to_text())?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, MovieError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Feeds the frames of a movie back into a [`Cpu`].
+pub struct Player {
+    movie: Movie,
+    frame: usize,
+    // fail when the screen differs from the recording
+    verify: bool,
+}
+
+impl Player {
+    pub fn new(movie: Movie, verify: bool) -> Self {
+        Self {
+            movie,
+            frame: 0,
+            verify,
+        }
+    }
+
+    // frames replayed so far
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame == self.movie.frames.len()
+    }
+
+    /// Replays the next frame, doing nothing once the movie is over.
+    pub fn run_frame(&mut self, cpu: &mut Cpu) -> Result<(), MovieError> {
+        if self.frame == 0 && cpu.rom_hash() != self.movie.rom_hash {
+            return Err(MovieError::RomMismatch);
+        }
+
+        let frame = match self.movie.frames.get(self.frame) {
+            Some(frame) => *frame,
+            None => return Ok(()),
+        };
+
+        // a released key was down before, even if it was tapped in between
+        // two frames
+        for key in 0..16 {
+            if frame.released & 1 << key != 0 {
+                cpu.key_press(key);
+                cpu.key_release(key);
+            }
+        }
+        for key in 0..16 {
+            if frame.keypad & 1 << key != 0 {
+                cpu.key_press(key);
+            } else {
+                cpu.key_release(key);
+            }
+        }
+
+        for _ in 0..frame.ticks {
+            cpu.tick()?;
+        }
+        cpu.tick_timers();
+        self.frame += 1;
+
+        if self.verify && display_hash(cpu.display()) != frame.display_hash {
+            return Err(MovieError::Desync {
+                frame: self.frame - 1,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn keypad_bits(keypad: &[bool; 16]) -> u16 {
+    (0..16)
+        .filter(|&key| keypad[key])
+        .fold(0, |bits, key| bits | 1 << key)
+}
+
+// 64 bit FNV-1a of the resolution and pixels
+fn display_hash(display: &Display) -> u64 {
+    let size = [display.width() as u8, display.height() as u8];
+
+    size.iter()
+        .chain(display.iter())
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+// the value of the next header line, which has to be `name`
+fn field<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    name: &str,
+) -> Result<(usize, &'a str), MovieError> {
+    let (line, text) = lines
+        .next()
+        .ok_or_else(|| parse_error(0, "unexpected end"))?;
+    let (key, value) = text.split_once(' ').unwrap_or((text, ""));
+
+    if key == name {
+        Ok((line, value))
+    } else {
+        Err(parse_error(line, &format!("expected {}", name)))
+    }
+}
+
+fn parse_error(line: usize, message: &str) -> MovieError {
+    MovieError::Parse {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn parse_frame(text: &str) -> Option<MovieFrame> {
+    let mut words = text.split_whitespace();
+    let frame = MovieFrame {
+        keypad: u16::from_str_radix(words.next()?, 16).ok()?,
+        released: u16::from_str_radix(words.next()?, 16).ok()?,
+        ticks: words.next()?.parse().ok()?,
+        display_hash: u64::from_str_radix(words.next()?, 16).ok()?,
+    };
+
+    words.next().is_none().then_some(frame)
+}
+
+fn parse_hash(text: &str) -> Option<[u8; 20]> {
+    if text.len() != 40 || !text.is_ascii() {
+        return None;
+    }
+
+    let mut hash = [0; 20];
+    for (index, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[index * 2..index * 2 + 2], 16).ok()?;
+    }
+
+    Some(hash)
+}
+
+// quirks a movie doesn't mention keep their default
+fn quirks_from_text(text: &str) -> Result<Quirks, String> {
+    let mut quirks = Quirks::default();
+
+    for field in text.split_whitespace() {
+        let (name, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("invalid quirk: {}", field))?;
+        quirks.set(name, value)?;
+    }
+
+    Ok(quirks)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,173 @@
+use super::*;
+use crate::quirks::MemoryIncrement;
+
+// draws a random digit whenever key 1 is down
+const PROGRAM: [u8; 14] = [
+    0x61, 0x01, 0xE1, 0x9E, 0x12, 0x00, 0xC0, 0x0F, 0xF0, 0x29, 0xD2, 0x25, 0x12, 0x00,
+];
+
+fn set_up_cpu(seed: u64) -> Cpu {
+    let mut cpu = Cpu::with_seed(Platform::SuperChip, Quirks::SUPER_CHIP, seed);
+    cpu.load_program(&PROGRAM).unwrap();
+
+    cpu
+}
+
+fn record(seed: u64) -> (Movie, Cpu) {
+    let mut cpu = set_up_cpu(seed);
+    let mut movie = Movie::new(&cpu);
+
+    for frame in 0..20 {
+        if frame % 5 == 0 {
+            cpu.key_press(1);
+        } else {
+            cpu.key_release(1);
+        }
+
+        let ticks = 8 + frame % 3;
+        for _ in 0..ticks {
+            cpu.tick().unwrap();
+        }
+        cpu.tick_timers();
+        movie.record_frame(&mut cpu, ticks);
+    }
+
+    (movie, cpu)
+}
+
+fn replay(movie: &Movie, cpu: &mut Cpu) -> Result<(), MovieError> {
+    let mut player = Player::new(movie.clone(), true);
+
+    while !player.is_finished() {
+        player.run_frame(cpu)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_text_roundtrip() {
+    let mut movie = record(7).0;
+    movie.quirks.memory_increment = MemoryIncrement::ByX;
+
+    let text = movie.to_text();
+
+    assert!(text.starts_with("chip8-movie 1\nrom "));
+    assert_eq!(Movie::parse(&text).unwrap(), movie);
+}
+
+#[test]
+fn test_parse_errors() {
+    let text = record(7).0.to_text();
+
+    assert!(matches!(
+        Movie::parse("hello"),
+        Err(MovieError::Parse { line: 1, .. })
+    ));
+    assert!(matches!(
+        Movie::parse(&text.replace("platform schip", "platform nes")),
+        Err(MovieError::Parse { line: 3, .. })
+    ));
+    assert!(matches!(
+        Movie::parse(&format!("{}0001 10\n", text)),
+        Err(MovieError::Parse { line: 28, .. })
+    ));
+}
+
+#[test]
+fn test_parse_rejects_other_rng() {
+    let text = record(7)
+        .0
+        .to_text()
+        .replace("rng splitmix64", "rng xorshift");
+
+    assert!(matches!(
+        Movie::parse(&text),
+        Err(MovieError::UnsupportedRng(name)) if name == "xorshift"
+    ));
+}
+
+#[test]
+fn test_missing_quirks_use_defaults() {
+    let text = record(7).0.to_text().replace(" jump_uses_vx=1", "");
+
+    assert!(!Movie::parse(&text).unwrap().quirks.jump_uses_vx);
+}
+
+#[test]
+fn test_replay() {
+    let (movie, original) = record(7);
+    let mut cpu = movie.cpu();
+    cpu.load_program(&PROGRAM).unwrap();
+
+    replay(&movie, &mut cpu).unwrap();
+
+    assert_eq!(cpu.save_state(), original.save_state());
+}
+
+#[test]
+fn test_replay_detects_divergence() {
+    let mut movie = record(7).0;
+    movie.seed = 8;
+    let mut cpu = movie.cpu();
+    cpu.load_program(&PROGRAM).unwrap();
+
+    assert!(matches!(
+        replay(&movie, &mut cpu),
+        Err(MovieError::Desync { frame: 0 })
+    ));
+}
+
+#[test]
+fn test_replay_checks_rom() {
+    let movie = record(7).0;
+    let mut cpu = movie.cpu();
+    cpu.load_program(&[0x12, 0x00]).unwrap();
+
+    assert!(matches!(
+        replay(&movie, &mut cpu),
+        Err(MovieError::RomMismatch)
+    ));
+}
+
+// draws the digit of the key FX0A got
+const WAIT_PROGRAM: [u8; 8] = [0xF0, 0x0A, 0xF0, 0x29, 0xD1, 0x15, 0x12, 0x06];
+
+#[test]
+fn test_replay_key_tapped_between_frames() {
+    let mut cpu = Cpu::with_seed(Platform::Chip8, Quirks::COSMAC_VIP, 7);
+    cpu.load_program(&WAIT_PROGRAM).unwrap();
+    let mut movie = Movie::new(&cpu);
+
+    for frame in 0..4 {
+        if frame == 1 {
+            cpu.key_press(7);
+            cpu.key_release(7);
+        }
+
+        let instructions = cpu.instructions();
+        cpu.run_frame(10).unwrap();
+        let ticks = (cpu.instructions() - instructions) as usize;
+        movie.record_frame(&mut cpu, ticks);
+    }
+
+    assert_eq!(movie.frames[1].keypad, 0);
+    assert_eq!(movie.frames[1].released, 1 << 7);
+    assert_ne!(movie.frames[3].display_hash, movie.frames[0].display_hash);
+
+    let movie = Movie::parse(&movie.to_text()).unwrap();
+    let mut replayed = movie.cpu();
+    replayed.load_program(&WAIT_PROGRAM).unwrap();
+    replay(&movie, &mut replayed).unwrap();
+    assert_eq!(replayed.save_state(), cpu.save_state());
+
+    // without the release FX0A never stops waiting
+    let mut movie = movie;
+    movie.frames[1].released = 0;
+    let mut replayed = movie.cpu();
+    replayed.load_program(&WAIT_PROGRAM).unwrap();
+    assert!(matches!(
+        replay(&movie, &mut replayed),
+        Err(MovieError::Desync { .. })
+    ));
+}
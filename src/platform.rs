@@ -0,0 +1,62 @@
+use crate::mem::{MEM_SIZE, XO_MEM_SIZE};
+use crate::quirks::Quirks;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+impl Platform {
+    // Octo's file extension conventions
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("sc8") => Platform::SuperChip,
+            Some("xo8") => Platform::XoChip,
+            _ => Platform::Chip8,
+        }
+    }
+
+    // the same names as the quirks presets of these platforms
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "chip8" => Some(Platform::Chip8),
+            "schip" => Some(Platform::SuperChip),
+            "xochip" => Some(Platform::XoChip),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Chip8 => "chip8",
+            Platform::SuperChip => "schip",
+            Platform::XoChip => "xochip",
+        }
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        match self {
+            Platform::Chip8 => Quirks::default(),
+            Platform::SuperChip => Quirks::SUPER_CHIP,
+            Platform::XoChip => Quirks::XO_CHIP,
+        }
+    }
+
+    pub fn memory_size(&self) -> usize {
+        match self {
+            Platform::XoChip => XO_MEM_SIZE,
+            _ => MEM_SIZE,
+        }
+    }
+
+    pub fn has_schip_opcodes(&self) -> bool {
+        *self != Platform::Chip8
+    }
+
+    pub fn has_xo_opcodes(&self) -> bool {
+        *self == Platform::XoChip
+    }
+}
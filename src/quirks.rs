@@ -0,0 +1,156 @@
+use std::fmt;
+
+/// How `FX55`/`FX65` leave the I register after a load/store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryIncrement {
+    /// I is left untouched (SUPER-CHIP)
+    Unchanged,
+    /// I is incremented by X (CHIP-48)
+    ByX,
+    /// I is incremented by X + 1 (COSMAC VIP)
+    ByXPlusOne,
+}
+
+/// Behaviors that differ between CHIP-8 interpreters.
+///
+/// Every platform answers these questions differently and ROMs rely on the
+/// answers of the platform they were written for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quirks {
+    /// `8XY6`/`8XYE` shift VY into VX instead of shifting VX in place
+    pub shift_uses_vy: bool,
+    /// how `FX55`/`FX65` modify I
+    pub memory_increment: MemoryIncrement,
+    /// `BNNN` jumps to XNN + VX instead of NNN + V0
+    pub jump_uses_vx: bool,
+    /// `8XY1`/`8XY2`/`8XY3` reset VF to 0
+    pub logic_resets_vf: bool,
+    /// `DXYN` clips sprites at the screen edge instead of wrapping them around
+    pub clip_sprites: bool,
+    /// `FX0A` waits for a key to be pressed and released, not only pressed
+    pub wait_for_release: bool,
+    /// `DXYN` waits for the next frame, like the VIP waiting for vertical blank
+    pub display_wait: bool,
+}
+
+impl Quirks {
+    pub const COSMAC_VIP: Quirks = Quirks {
+        shift_uses_vy: true,
+        memory_increment: MemoryIncrement::ByXPlusOne,
+        jump_uses_vx: false,
+        logic_resets_vf: true,
+        clip_sprites: true,
+        wait_for_release: true,
+        display_wait: true,
+    };
+
+    pub const CHIP_48: Quirks = Quirks {
+        shift_uses_vy: false,
+        memory_increment: MemoryIncrement::ByX,
+        jump_uses_vx: true,
+        logic_resets_vf: false,
+        clip_sprites: true,
+        wait_for_release: false,
+        display_wait: false,
+    };
+
+    pub const SUPER_CHIP: Quirks = Quirks {
+        shift_uses_vy: false,
+        memory_increment: MemoryIncrement::Unchanged,
+        jump_uses_vx: true,
+        logic_resets_vf: false,
+        clip_sprites: true,
+        wait_for_release: false,
+        display_wait: false,
+    };
+
+    pub const XO_CHIP: Quirks = Quirks {
+        shift_uses_vy: true,
+        memory_increment: MemoryIncrement::ByXPlusOne,
+        jump_uses_vx: false,
+        logic_resets_vf: false,
+        clip_sprites: false,
+        wait_for_release: true,
+        display_wait: false,
+    };
+
+    pub fn from_name(name: &str) -> Option<Quirks> {
+        match name {
+            "vip" => Some(Self::COSMAC_VIP),
+            "chip48" => Some(Self::CHIP_48),
+            "schip" => Some(Self::SUPER_CHIP),
+            "xochip" => Some(Self::XO_CHIP),
+            _ => None,
+        }
+    }
+
+    /// Changes one quirk by the name of its field, flags take `0` or `1` and
+    /// `memory_increment` takes `unchanged`, `x` or `x+1`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let flag = match value {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        };
+        let invalid = || format!("invalid value for {}: {}", name, value);
+
+        match name {
+            "memory_increment" => {
+                self.memory_increment = match value {
+                    "unchanged" => MemoryIncrement::Unchanged,
+                    "x" => MemoryIncrement::ByX,
+                    "x+1" => MemoryIncrement::ByXPlusOne,
+                    _ => return Err(invalid()),
+                }
+            }
+            "shift_uses_vy" => self.shift_uses_vy = flag.ok_or_else(invalid)?,
+            "jump_uses_vx" => self.jump_uses_vx = flag.ok_or_else(invalid)?,
+            "logic_resets_vf" => self.logic_resets_vf = flag.ok_or_else(invalid)?,
+            "clip_sprites" => self.clip_sprites = flag.ok_or_else(invalid)?,
+            "wait_for_release" => self.wait_for_release = flag.ok_or_else(invalid)?,
+            "display_wait" => self.display_wait = flag.ok_or_else(invalid)?,
+            _ => return Err(format!("unknown quirk: {}", name)),
+        }
+
+        Ok(())
+    }
+}
+
+// `name=value` pairs in the format `set` reads
+impl fmt::Display for Quirks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let increment = match self.memory_increment {
+            MemoryIncrement::Unchanged => "unchanged",
+            MemoryIncrement::ByX => "x",
+            MemoryIncrement::ByXPlusOne => "x+1",
+        };
+
+        write!(
+            f,
+            "shift_uses_vy={} memory_increment={} jump_uses_vx={} logic_resets_vf={} clip_sprites={} \
+             wait_for_release={} display_wait={}",
+            self.shift_uses_vy as u8,
+            increment,
+            self.jump_uses_vx as u8,
+            self.logic_resets_vf as u8,
+            self.clip_sprites as u8,
+            self.wait_for_release as u8,
+            self.display_wait as u8
+        )
+    }
+}
+
+impl Default for Quirks {
+    // the behavior this emulator has always had
+    fn default() -> Self {
+        Self {
+            shift_uses_vy: false,
+            memory_increment: MemoryIncrement::Unchanged,
+            jump_uses_vx: false,
+            logic_resets_vf: false,
+            clip_sprites: true,
+            wait_for_release: false,
+            display_wait: false,
+        }
+    }
+}
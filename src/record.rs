@@ -0,0 +1,120 @@
+use crate::cpu::{HIRES_SCREEN_HEIGHT, HIRES_SCREEN_WIDTH};
+use crate::display::{Display, Palette};
+use std::io::{self, Write};
+
+const FRAME_RATE: u64 = 60;
+// gif delays are in hundredths of a second
+const TIME_UNITS: u64 = 100;
+
+/// Records the screen as an animated GIF playing at 60 frames per second.
+///
+/// Every frame is drawn at the high resolution size, so the recording keeps
+/// going when a rom switches between resolutions.
+pub struct GifRecorder<W: Write> {
+    encoder: gif::Encoder<W>,
+    scale: usize,
+    // the last screen, written once it changes and its duration is known
+    pending: Option<Vec<u8>>,
+    pending_since: u64,
+    frames: u64,
+}
+
+impl<W: Write> GifRecorder<W> {
+    pub fn new(out: W, palette: &Palette, scale: usize) -> io::Result<Self> {
+        let width = (HIRES_SCREEN_WIDTH * scale) as u16;
+        let height = (HIRES_SCREEN_HEIGHT * scale) as u16;
+        let colors: Vec<u8> = palette.0.concat();
+
+        let mut encoder = gif::Encoder::new(out, width, height, &colors).map_err(to_io_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(to_io_error)?;
+
+        Ok(Self {
+            encoder,
+            scale,
+            pending: None,
+            pending_since: 0,
+            frames: 0,
+        })
+    }
+
+    // frames recorded so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Adds one 60th of a second of `display`, unchanged screens only make
+    /// the previous frame last longer.
+    pub fn record_frame(&mut self, display: &Display) -> io::Result<()> {
+        let pixels = self.scale_up(display);
+
+        if self.pending.as_ref() != Some(&pixels) {
+            self.write_pending()?;
+            self.pending = Some(pixels);
+            self.pending_since = self.frames;
+        }
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    /// Writes the last frame and the end of the GIF.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_pending()?;
+
+        self.encoder.into_inner()
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        let pixels = match self.pending.take() {
+            Some(pixels) => pixels,
+            None => return Ok(()),
+        };
+
+        // rounding the start and end instead of the duration keeps the
+        // average at 60 frames per second with delays of 1 and 2
+        let start = self.pending_since * TIME_UNITS / FRAME_RATE;
+        let end = self.frames * TIME_UNITS / FRAME_RATE;
+
+        let mut frame = gif::Frame::from_indexed_pixels(
+            (HIRES_SCREEN_WIDTH * self.scale) as u16,
+            (HIRES_SCREEN_HEIGHT * self.scale) as u16,
+            pixels,
+            None,
+        );
+        frame.delay = (end - start).clamp(1, u16::MAX as u64) as u16;
+
+        self.encoder.write_frame(&frame).map_err(to_io_error)
+    }
+
+    // palette indices at the recording size
+    fn scale_up(&self, display: &Display) -> Vec<u8> {
+        let factor = self.scale * HIRES_SCREEN_WIDTH / display.width();
+        let mut pixels =
+            Vec::with_capacity(HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT * self.scale * self.scale);
+
+        for row in display.chunks(display.width()) {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|pixel| std::iter::repeat_n(*pixel, factor))
+                .collect();
+
+            for _ in 0..factor {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        pixels
+    }
+}
+
+fn to_io_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        gif::EncodingError::Format(err) => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,52 @@
+use super::*;
+
+// delays and the first pixel of every frame
+fn decode_gif(data: &[u8]) -> (u16, u16, Vec<(u16, u8)>) {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(data).unwrap();
+    let (width, height) = (decoder.width(), decoder.height());
+
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push((frame.delay, frame.buffer[0]));
+    }
+
+    (width, height, frames)
+}
+
+#[test]
+fn test_record_frames() {
+    let mut display = Display::new(64, 32);
+    let mut recorder = GifRecorder::new(Vec::new(), &Palette::default(), 1).unwrap();
+
+    for _ in 0..3 {
+        recorder.record_frame(&display).unwrap();
+    }
+    display[0] = 1;
+    recorder.record_frame(&display).unwrap();
+    display[0] = 2;
+    recorder.record_frame(&display).unwrap();
+
+    assert_eq!(recorder.frames(), 5);
+    assert_eq!(
+        decode_gif(&recorder.finish().unwrap()),
+        (128, 64, vec![(5, 0), (1, 1), (2, 2)])
+    );
+}
+
+#[test]
+fn test_record_resolution_change() {
+    let mut display = Display::new(64, 32);
+    display[1] = 1;
+    let mut recorder = GifRecorder::new(Vec::new(), &Palette::default(), 2).unwrap();
+
+    recorder.record_frame(&display).unwrap();
+    display.resize(128, 64);
+    recorder.record_frame(&display).unwrap();
+
+    let (width, height, frames) = decode_gif(&recorder.finish().unwrap());
+
+    assert_eq!((width, height), (256, 128));
+    assert_eq!(frames.len(), 2);
+}
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+// how to get back from a state to the one pushed before it
+enum Step {
+    // run length encoded xor of both states
+    Delta(Vec<u8>),
+    // the state changed size, e.g. after a resolution switch
+    Full(Vec<u8>),
+}
+
+/// Ring buffer of save states from [`Cpu::save_state`](crate::Cpu::save_state).
+///
+/// Only the newest state is kept whole, older ones are stored as compressed
+/// differences to the state that followed them.
+pub struct Rewind {
+    capacity: usize,
+    current: Option<Vec<u8>>,
+    steps: VecDeque<Step>,
+}
+
+impl Rewind {
+    // capacity is the number of states that can be stepped back through
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            current: None,
+            steps: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.steps.clear();
+    }
+
+    pub fn push(&mut self, state: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(current) = self.current.take() {
+            let step = if current.len() == state.len() {
+                Step::Delta(encode_delta(&state, &current))
+            } else {
+                Step::Full(current)
+            };
+
+            if self.steps.len() == self.capacity {
+                self.steps.pop_front();
+            }
+            self.steps.push_back(step);
+        }
+
+        self.current = Some(state);
+    }
+
+    // the state pushed before the newest one, which it then replaces
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        let step = self.steps.pop_back()?;
+        let current = self.current.as_mut()?;
+
+        match step {
+            Step::Delta(delta) => apply_delta(&delta, current),
+            Step::Full(state) => *current = state,
+        }
+
+        Some(current.clone())
+    }
+}
+
+// alternating runs of unchanged and changed bytes, every run starts with its
+// length as a LEB128 number and changed runs are followed by their xor
+fn encode_delta(from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+
+    while pos < from.len() {
+        let unchanged = from[pos..]
+            .iter()
+            .zip(&to[pos..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        pos += unchanged;
+
+        let changed = from[pos..]
+            .iter()
+            .zip(&to[pos..])
+            .take_while(|(a, b)| a != b)
+            .count();
+
+        write_length(&mut out, unchanged);
+        write_length(&mut out, changed);
+        out.extend((pos..pos + changed).map(|i| from[i] ^ to[i]));
+        pos += changed;
+    }
+
+    out
+}
+
+fn apply_delta(delta: &[u8], state: &mut [u8]) {
+    let mut delta = delta.iter().copied();
+    let mut pos = 0;
+
+    while let Some(unchanged) = read_length(&mut delta) {
+        pos += unchanged;
+
+        let changed = read_length(&mut delta).unwrap_or(0);
+        for byte in &mut state[pos..pos + changed] {
+            *byte ^= delta.next().unwrap_or(0);
+        }
+        pos += changed;
+    }
+}
+
+fn write_length(out: &mut Vec<u8>, mut length: usize) {
+    while length >= 0x80 {
+        out.push(length as u8 | 0x80);
+        length >>= 7;
+    }
+    out.push(length as u8);
+}
+
+fn read_length(bytes: &mut impl Iterator<Item = u8>) -> Option<usize> {
+    let mut length = 0;
+    let mut shift = 0;
+
+    loop {
+        let byte = bytes.next()?;
+        length |= ((byte & 0x7F) as usize) << shift;
+        shift += 7;
+
+        if byte & 0x80 == 0 {
+            return Some(length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,61 @@
+use super::*;
+
+#[test]
+fn test_pop_returns_older_states() {
+    let mut rewind = Rewind::new(8);
+    rewind.push(vec![1, 2, 3, 4]);
+    rewind.push(vec![1, 9, 3, 4]);
+    rewind.push(vec![1, 9, 3, 7]);
+
+    assert_eq!(rewind.pop(), Some(vec![1, 9, 3, 4]));
+    assert_eq!(rewind.pop(), Some(vec![1, 2, 3, 4]));
+    assert_eq!(rewind.pop(), None);
+}
+
+#[test]
+fn test_capacity() {
+    let mut rewind = Rewind::new(2);
+    for value in 0..5 {
+        rewind.push(vec![value; 3]);
+    }
+
+    assert_eq!(rewind.len(), 2);
+    assert_eq!(rewind.pop(), Some(vec![3; 3]));
+    assert_eq!(rewind.pop(), Some(vec![2; 3]));
+    assert!(rewind.is_empty());
+}
+
+#[test]
+fn test_size_change() {
+    let mut rewind = Rewind::new(4);
+    rewind.push(vec![1, 2]);
+    rewind.push(vec![1, 2, 3, 4]);
+
+    assert_eq!(rewind.pop(), Some(vec![1, 2]));
+}
+
+#[test]
+fn test_pushing_after_pop() {
+    let mut rewind = Rewind::new(4);
+    rewind.push(vec![0; 4]);
+    rewind.push(vec![1; 4]);
+    rewind.pop();
+    rewind.push(vec![2; 4]);
+
+    assert_eq!(rewind.pop(), Some(vec![0; 4]));
+}
+
+#[test]
+fn test_delta_is_small() {
+    let mut from = vec![0u8; 4096];
+    let to = from.clone();
+    from[300] = 0xAA;
+    from[3000] = 0x55;
+
+    let delta = encode_delta(&from, &to);
+    let mut state = to;
+    apply_delta(&delta, &mut state);
+
+    assert!(delta.len() < 16);
+    assert_eq!(state, from);
+}
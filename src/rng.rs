@@ -0,0 +1,37 @@
+// stored in input movies, which only replay with the same numbers; it has to
+// change whenever a seed starts giving different ones
+pub const ALGORITHM: &str = "splitmix64";
+
+/// SplitMix64 generator behind `CXNN`.
+///
+/// Its whole state is a single u64, so it can be stored in save states and
+/// replays to make random numbers reproducible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    // the generator continues from here when passed to Rng::new
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+}